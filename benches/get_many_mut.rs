@@ -0,0 +1,47 @@
+// Compare the checked get_many_mut with the unchecked version and the split_at_mut workaround
+// Run with : cargo bench --bench get_many_mut
+use std::hint::black_box;
+use std::time::Instant;
+
+use aoc_2022::utils::{get_many_mut, get_many_unchecked_mut};
+
+const LEN: usize = 16;
+const NRUN: usize = 10_000_000;
+
+fn measure(name: &str, mut func: impl FnMut(&mut [u64], usize)) {
+  let mut values = [0u64; LEN];
+  let now = Instant::now();
+  for i in 0..NRUN {
+    func(black_box(&mut values), i);
+  }
+  let duration = now.elapsed();
+  black_box(values);
+  println!("{: <30} in {:>8.3} ns/iter", name, duration.as_nanos() as f64 / NRUN as f64);
+}
+
+fn main() {
+  measure("get_many_mut", |values, i| {
+    let idx = i % (LEN - 2);
+    if let Ok([a, b, c]) = get_many_mut(values, [idx, idx + 1, idx + 2]) {
+      *a += *b + *c;
+      *c += 1;
+    }
+  });
+
+  measure("get_many_unchecked_mut", |values, i| {
+    let idx = i % (LEN - 2);
+    // SAFETY: idx + 2 < LEN and indexes are distinct
+    let [a, b, c] = unsafe { get_many_unchecked_mut(values, [idx, idx + 1, idx + 2]) };
+    *a += *b + *c;
+    *c += 1;
+  });
+
+  measure("split_at_mut", |values, i| {
+    let idx = i % (LEN - 2);
+    let (left, right) = values.split_at_mut(idx + 1);
+    let (middle, right) = right.split_at_mut(1);
+    let (a, b, c) = (&mut left[idx], &mut middle[0], &mut right[0]);
+    *a += *b + *c;
+    *c += 1;
+  });
+}
@@ -0,0 +1,137 @@
+// Benchmark every registered solver on its real input (data/dayNN.txt, generated when missing)
+// Run with : cargo bench --bench solvers, or cargo bench -- day15 to only run the names containing day15
+// Results are written in target/bench/solvers.csv and compared with the previous run.
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use aoc_2022::bench::{self, Stats};
+use aoc_2022::generate;
+use aoc_2022::input::Input;
+use aoc_2022::registry;
+use aoc_2022::timing::format_nanos;
+use aoc_2022::Result;
+
+/// Input of a day, the same file as the runner or a generated one of the same size
+fn input(day: u32) -> Result<Input> {
+  let path = PathBuf::from(format!("data/day{:02}.txt", day));
+  if path.exists() {
+    return Input::load(path);
+  }
+  let content = generate::generate(day, &generate::Options::default())?;
+  Ok(Input::from_bytes(
+    format!("day{:02}_generated", day),
+    content.into_bytes(),
+  ))
+}
+
+fn results_path() -> PathBuf {
+  let target = env::var("CARGO_TARGET_DIR").unwrap_or(String::from("target"));
+  Path::new(&target).join("bench").join("solvers.csv")
+}
+
+const HEADER: &str = "name,input_bytes,iterations,mean_ns,median_ns,low_ns,high_ns,samples,outliers";
+
+/// Mean and confidence interval of the previous run, by name
+fn previous_results(path: &Path) -> HashMap<String, (f64, f64, f64)> {
+  let content = fs::read_to_string(path).unwrap_or_default();
+  content
+    .lines()
+    .skip(1)
+    .filter_map(|line| {
+      let fields = line.split(',').collect::<Vec<_>>();
+      let number = |index: usize| fields.get(index)?.parse::<f64>().ok();
+      Some((fields[0].to_string(), (number(3)?, number(5)?, number(6)?)))
+    })
+    .collect()
+}
+
+/// Change against the previous mean, only significant when the intervals don't overlap
+fn change(stats: &Stats, previous: Option<&(f64, f64, f64)>) -> String {
+  match previous {
+    Some((mean, low, high)) => {
+      let percent = (stats.mean / mean - 1.) * 100.;
+      let verdict = if stats.high < *low {
+        "faster"
+      } else if stats.low > *high {
+        "slower"
+      } else {
+        "no change"
+      };
+      format!("{:+6.1}% {}", percent, verdict)
+    }
+    None => String::new(),
+  }
+}
+
+fn main() -> Result<()> {
+  // cargo passes `--bench`, everything else not starting with `-` is a filter
+  let filters = env::args()
+    .skip(1)
+    .filter(|arg| !arg.starts_with('-'))
+    .collect::<Vec<_>>();
+  let config = bench::Config::default();
+  let path = results_path();
+  let mut previous = previous_results(&path);
+  let mut inputs = HashMap::new();
+  let mut lines = vec![String::from(HEADER)];
+
+  for (name, solver) in registry::all() {
+    if !filters.is_empty() && !filters.iter().any(|filter| name.contains(filter.as_str())) {
+      continue;
+    }
+    let Some(day) = registry::day_of(name) else {
+      continue;
+    };
+    if let Entry::Vacant(entry) = inputs.entry(day) {
+      entry.insert(input(day)?);
+    }
+    let input = &inputs[&day];
+    if let Err(e) = solver(input) {
+      eprintln!("{: <30} skipped: {}", name, e);
+      continue;
+    }
+    let stats = bench::run_with_output(&config, || solver(input));
+    println!(
+      "{: <30} {:>10} [{:>10} {:>10}] {:>2} outliers {}",
+      name,
+      format_nanos(stats.mean),
+      format_nanos(stats.low),
+      format_nanos(stats.high),
+      stats.outliers,
+      change(&stats, previous.get(name))
+    );
+    previous.remove(name);
+    lines.push(format!(
+      "{},{},{},{:.1},{:.1},{:.1},{:.1},{},{}",
+      name,
+      input.size()?,
+      stats.iterations,
+      stats.mean,
+      stats.median,
+      stats.low,
+      stats.high,
+      stats.samples.len(),
+      stats.outliers
+    ));
+  }
+
+  // keep the results of the solvers filtered out of this run
+  if let Ok(content) = fs::read_to_string(&path) {
+    lines.extend(
+      content
+        .lines()
+        .skip(1)
+        .filter(|line| line.split(',').next().is_some_and(|name| previous.contains_key(name)))
+        .map(String::from),
+    );
+  }
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(&path, lines.join("\n") + "\n")?;
+  println!("results written in {}", path.display());
+  Ok(())
+}
@@ -0,0 +1,112 @@
+// Compare the byte tokenizer with the std approach (split + parse and regex)
+// Run with : cargo bench --bench tokenizer
+use std::hint::black_box;
+use std::time::Instant;
+
+use regex::Regex;
+
+use aoc_2022::tokenizer::{lines, parse_u64, Tokenizer};
+
+const NRUN: usize = 20;
+
+fn measure(name: &str, input: &str, func: impl Fn(&str) -> u64) {
+  let mut result = 0;
+  let now = Instant::now();
+  for _ in 0..NRUN {
+    result = func(black_box(input));
+  }
+  let duration = now.elapsed();
+  println!(
+    "{: <30} in {:>8.2} us : {:>6.2} ns/byte result={}",
+    name,
+    duration.as_micros() as f64 / NRUN as f64,
+    duration.as_nanos() as f64 / (NRUN * input.len()) as f64,
+    result
+  );
+}
+
+/// Deterministic pseudo random numbers, enough to avoid branch predictor learning the input
+fn numbers(count: usize, modulo: u64) -> impl Iterator<Item = u64> {
+  let mut state = 0x2545_f491_4f6c_dd1du64;
+  (0..count).map(move |_| {
+    state ^= state << 13;
+    state ^= state >> 7;
+    state ^= state << 17;
+    state % modulo
+  })
+}
+
+fn main() {
+  let calories = numbers(200_000, 60_000)
+    .map(|value| if value < 6_000 { String::new() } else { value.to_string() })
+    .collect::<Vec<_>>()
+    .join("\n");
+  measure("calories std", &calories, |input| {
+    input.lines().filter_map(|line| line.parse::<u64>().ok()).sum()
+  });
+  measure("calories tokenizer", &calories, |input| {
+    lines(input.as_bytes()).filter_map(|line| parse_u64(line).ok()).sum()
+  });
+
+  let values = numbers(300_000, 50).collect::<Vec<_>>();
+  let moves = values
+    .chunks(3)
+    .map(|chunk| format!("move {} from {} to {}", chunk[0], chunk[1], chunk[2]))
+    .collect::<Vec<_>>()
+    .join("\n");
+  measure("moves split", &moves, |input| {
+    let mut total = 0;
+    for line in input.lines() {
+      let mut splitted = line.split(' ');
+      splitted.next();
+      total += splitted.next().unwrap().parse::<u64>().unwrap();
+      splitted.next();
+      total += splitted.next().unwrap().parse::<u64>().unwrap();
+      splitted.next();
+      total += splitted.next().unwrap().parse::<u64>().unwrap();
+    }
+    total
+  });
+  measure("moves tokenizer", &moves, |input| {
+    let mut total = 0;
+    for line in lines(input.as_bytes()) {
+      let mut tokens = Tokenizer::new(line);
+      total += tokens.next_u64().unwrap();
+      total += tokens.next_u64().unwrap();
+      total += tokens.next_u64().unwrap();
+    }
+    total
+  });
+
+  let values = numbers(40_000, 4_000_000).collect::<Vec<_>>();
+  let sensors = values
+    .chunks(4)
+    .map(|chunk| {
+      format!(
+        "Sensor at x={}, y=-{}: closest beacon is at x=-{}, y={}",
+        chunk[0], chunk[1], chunk[2], chunk[3]
+      )
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+  let regex = Regex::new(r"(-?\d+)").unwrap();
+  measure("sensors regex", &sensors, |input| {
+    let mut total = 0;
+    for line in input.lines() {
+      for capture in regex.captures_iter(line) {
+        total += capture[0].parse::<i64>().unwrap();
+      }
+    }
+    total as u64
+  });
+  measure("sensors tokenizer", &sensors, |input| {
+    let mut total = 0;
+    for line in lines(input.as_bytes()) {
+      let mut tokens = Tokenizer::new(line);
+      while let Some(value) = tokens.next_i64() {
+        total += value;
+      }
+    }
+    total as u64
+  });
+}
@@ -0,0 +1,165 @@
+//! Measurement of the `solvers` bench target: calibration, warmup, outlier rejection and
+//! bootstrap confidence intervals, without external crates.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::rng::Rng;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+  /// Time spent running the function before measuring, caches and branch predictors are warm after it
+  pub warmup: Duration,
+  /// Each sample repeats the function enough times to last at least this long
+  pub sample_time: Duration,
+  /// Number of samples, reduced for slow functions so a measure stays under `max_time`
+  pub samples: usize,
+  pub max_time: Duration,
+}
+
+impl Default for Config {
+  fn default() -> Self {
+    Self {
+      warmup: Duration::from_millis(300),
+      sample_time: Duration::from_millis(10),
+      samples: 50,
+      max_time: Duration::from_secs(5),
+    }
+  }
+}
+
+/// Less samples than this don't give a meaningful interval
+const MIN_SAMPLES: usize = 10;
+const BOOTSTRAP_RESAMPLES: usize = 1000;
+
+/// Time per iteration of a function, in nanoseconds
+#[derive(Debug, Clone)]
+pub struct Stats {
+  /// Iterations in a sample
+  pub iterations: u64,
+  /// Samples kept after outlier rejection
+  pub samples: Vec<f64>,
+  pub outliers: usize,
+  pub mean: f64,
+  pub median: f64,
+  /// 95% confidence interval of the mean
+  pub low: f64,
+  pub high: f64,
+}
+
+/// Value at `fraction` of sorted `values`, interpolated between the two closest ones
+fn quantile(sorted: &[f64], fraction: f64) -> f64 {
+  let position = fraction * (sorted.len() - 1) as f64;
+  let (below, above) = (position.floor() as usize, position.ceil() as usize);
+  sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
+
+/// Remove the samples outside of Tukey's fences (1.5 interquartile range away from the quartiles),
+/// they come from interruptions by the system rather than from the function
+pub fn reject_outliers(mut samples: Vec<f64>) -> (Vec<f64>, usize) {
+  samples.sort_by(f64::total_cmp);
+  let first = quantile(&samples, 0.25);
+  let third = quantile(&samples, 0.75);
+  let range = third - first;
+  let count = samples.len();
+  samples.retain(|sample| (first - 1.5 * range..=third + 1.5 * range).contains(sample));
+  let outliers = count - samples.len();
+  (samples, outliers)
+}
+
+fn mean(values: &[f64]) -> f64 {
+  values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Statistics of samples in nanoseconds per iteration, `samples` must not be empty
+pub fn summarize(iterations: u64, samples: Vec<f64>) -> Stats {
+  let (samples, outliers) = reject_outliers(samples);
+  // percentile bootstrap: the distribution of the mean of resampled samples
+  let mut rng = Rng::new(samples.len() as u64);
+  let mut means = (0..BOOTSTRAP_RESAMPLES)
+    .map(|_| {
+      (0..samples.len())
+        .map(|_| samples[rng.index(samples.len())])
+        .sum::<f64>()
+        / samples.len() as f64
+    })
+    .collect::<Vec<_>>();
+  means.sort_by(f64::total_cmp);
+  Stats {
+    iterations,
+    outliers,
+    mean: mean(&samples),
+    median: quantile(&samples, 0.5),
+    low: quantile(&means, 0.025),
+    high: quantile(&means, 0.975),
+    samples,
+  }
+}
+
+/// Run `iterations` times and return the time per iteration in nanoseconds
+fn sample(iterations: u64, function: &mut impl FnMut()) -> f64 {
+  let now = Instant::now();
+  for _ in 0..iterations {
+    function();
+  }
+  now.elapsed().as_nanos() as f64 / iterations as f64
+}
+
+/// Warm up, find the number of iterations per sample and measure `function`
+pub fn run(config: &Config, mut function: impl FnMut()) -> Stats {
+  // warmup, also gives a first estimation of the time per iteration
+  let now = Instant::now();
+  let mut runs = 0u64;
+  while runs == 0 || now.elapsed() < config.warmup {
+    function();
+    runs += 1;
+  }
+  let estimation = now.elapsed().as_nanos() as f64 / runs as f64;
+
+  // calibration: double the iterations until a sample is long enough
+  let target = config.sample_time.as_nanos() as f64;
+  let mut iterations = ((target / estimation.max(1.)) as u64).max(1);
+  loop {
+    let per_iteration = sample(iterations, &mut function);
+    if per_iteration * iterations as f64 >= target || iterations >= u64::MAX / 2 {
+      break;
+    }
+    iterations *= 2;
+  }
+
+  let sample_nanos = estimation.max(1.) * iterations as f64;
+  let affordable = (config.max_time.as_nanos() as f64 / sample_nanos) as usize;
+  let samples = (0..affordable.clamp(MIN_SAMPLES, config.samples.max(MIN_SAMPLES)))
+    .map(|_| sample(iterations, &mut function))
+    .collect::<Vec<_>>();
+  summarize(iterations, samples)
+}
+
+/// `run` for a function returning a value, the value is kept from being optimized away
+pub fn run_with_output<T>(config: &Config, mut function: impl FnMut() -> T) -> Stats {
+  run(config, || {
+    black_box(function());
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn outliers_are_rejected() {
+    let mut samples = (0..40).map(|i| 100. + (i % 5) as f64).collect::<Vec<_>>();
+    samples.push(1000.);
+    samples.push(5.);
+    let stats = summarize(1, samples);
+    assert_eq!(stats.outliers, 2);
+    assert_eq!(stats.median, 102.);
+    assert!(stats.low <= stats.mean && stats.mean <= stats.high);
+    assert!(stats.low > 100. && stats.high < 104.);
+  }
+
+  #[test]
+  fn constant_samples() {
+    let stats = summarize(3, vec![42.; 12]);
+    assert_eq!((stats.outliers, stats.low, stats.mean, stats.high), (0, 42., 42., 42.));
+  }
+}
@@ -1,15 +1,84 @@
 // #![allow(unused_variables)]
 
-use std::path::Path;
-
+use crate::input::{Input, LineReader};
+use crate::select::{largest, TopN};
+use crate::tokenizer::{lines, parse_u64};
 use crate::Result;
-use crate::utils::ReturnType;
+use crate::utils::Solution;
+
+fn checked_add(total: u64, value: u64) -> Result<u64> {
+  Ok(total.checked_add(value).ok_or("Calories overflow")?)
+}
+
+fn checked_sum(values: &[u64]) -> Result<u64> {
+  values.iter().try_fold(0, |total, value| checked_add(total, *value))
+}
+
+/// Part1 and part2 from the biggest inventories in decreasing order, less than 3 elves are fine
+fn solution(top3: &[u64]) -> Result<Solution<u64, u64>> {
+  let part1 = *top3.first().ok_or("No elf")?;
+  let part2 = checked_sum(top3)?;
+  Ok(Solution::new(part1, part2))
+}
+
+/// Inventory of an elf
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elf {
+  /// Position in the input, from 0
+  pub index: usize,
+  pub items: usize,
+  pub total: u64,
+  /// 1 for the biggest total, elves with the same total have the same rank
+  pub rank: usize,
+}
 
-pub fn day01(filename: &Path) -> Result<ReturnType> {
+/// Every elf of the input with its rank
+pub fn elves(input: &Input) -> Result<Vec<Elf>> {
+  let mut elves = Vec::new();
+  let (mut items, mut total, mut in_elf) = (0, 0, false);
+  for line in lines(&input.read_bytes()?).map(Some).chain([None]) {
+    match line {
+      Some(line) if !line.is_empty() => {
+        total = checked_add(total, parse_u64(line)?)?;
+        items += 1;
+        in_elf = true;
+        continue;
+      }
+      // the end of the input only closes a non empty elf, like the solvers
+      None if !in_elf => break,
+      _ => {}
+    }
+    elves.push(Elf {
+      index: elves.len(),
+      items,
+      total,
+      rank: 0,
+    });
+    (items, total, in_elf) = (0, 0, false);
+  }
+  let mut order = (0..elves.len()).collect::<Vec<_>>();
+  order.sort_by_key(|&index| std::cmp::Reverse(elves[index].total));
+  for (position, &index) in order.iter().enumerate() {
+    elves[index].rank = match position {
+      0 => 1,
+      _ if elves[order[position - 1]].total == elves[index].total => elves[order[position - 1]].rank,
+      _ => position + 1,
+    };
+  }
+  Ok(elves)
+}
+
+/// Calories carried by the `n` elves carrying the most, all the elves if there are less than `n`
+pub fn top_calories(input: &Input, n: usize) -> Result<u64> {
+  let totals = elves(input)?.iter().map(|elf| elf.total).collect::<Vec<_>>();
+  checked_sum(&largest(totals, n))
+}
+
+pub fn day01(input: &Input) -> Result<Solution<u64, u64>> {
 
   let mut input_puzzle = Vec::new();
   let mut one_elf = Vec::new();
-  for line in std::fs::read_to_string(filename)?.lines() {
+  for line in input.read_to_string()?.lines() {
     let line_str = line;
     if line_str.is_empty() {
       input_puzzle.push(one_elf.clone());
@@ -24,63 +93,113 @@ pub fn day01(filename: &Path) -> Result<ReturnType> {
     input_puzzle.push(one_elf);
   }
 
-  let mut prep_puzzle: Vec<u64> = input_puzzle.iter()
-    .map(|one_elf| one_elf.iter().sum())
-    .collect::<Vec<_>>();
-  prep_puzzle.sort();
-  prep_puzzle.reverse();
-
-  // part1
-  let part1: u64 = prep_puzzle[0];
-
-  // part2
-  let part2: u64 = prep_puzzle[0..3].iter().sum();
-
-  Ok(ReturnType::Numeric(part1, part2))
+  let prep_puzzle: Vec<u64> = input_puzzle.iter()
+    .map(|one_elf| checked_sum(one_elf))
+    .collect::<Result<Vec<_>>>()?;
+  // only the 3 biggest are needed, no need to sort everything
+  solution(&largest(prep_puzzle, 3))
 }
 
 
-pub fn day01_speed(filename: &Path) -> Result<ReturnType> {
+pub fn day01_speed(input: &Input) -> Result<Solution<u64, u64>> {
 
   let mut input_puzzle = Vec::new();
   let mut one_elf = 0;
-  for line in std::fs::read_to_string(filename)?.lines() {
-    let line_str = line;
-    if line_str.is_empty() {
+  let mut in_elf = false;
+  for line in lines(&input.read_bytes()?) {
+    if line.is_empty() {
       input_puzzle.push(one_elf);
       one_elf = 0;
+      in_elf = false;
       continue;
     }
-    let value = line_str.parse::<u64>()?;
-    one_elf += value;
+    let value = parse_u64(line)?;
+    one_elf = checked_add(one_elf, value)?;
+    in_elf = true;
   }
   // Push last elf inventory if the puzzle don't end with new line
-  if one_elf != 0 {
+  if in_elf {
     input_puzzle.push(one_elf);
   }
 
-  input_puzzle.sort();
-  input_puzzle.reverse();
-
-  // part1
-  let part1: u64 = input_puzzle[0];
+  solution(&largest(input_puzzle, 3))
+}
 
-  // part2
-  let part2: u64 = input_puzzle[0..3].iter().sum();
+// Streaming version, only the 3 biggest inventories are kept so memory doesn't depend on input size
+pub fn day01_stream(input: &Input) -> Result<Solution<u64, u64>> {
+  let mut lines = LineReader::new(input.reader()?);
+  let mut top3 = TopN::new(3);
+  let mut one_elf = 0;
+  let mut in_elf = false;
+  loop {
+    let line = lines.next_line()?;
+    if let Some(line_str) = line {
+      if !line_str.is_empty() {
+        one_elf = checked_add(one_elf, line_str.parse::<u64>()?)?;
+        in_elf = true;
+        continue;
+      }
+    }
+    // same elves as the other versions: a blank line always close an elf, the end of input only a non empty one
+    if line.is_some() || in_elf {
+      top3.push(one_elf);
+    }
+    in_elf = false;
+    one_elf = 0;
+    if line.is_none() {
+      break;
+    }
+  }
 
-  Ok(ReturnType::Numeric(part1, part2))
+  solution(&top3.into_sorted_vec())
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"1000
+2000
+3000
+
+4000
+
+5000
+6000
+
+7000
+8000
+9000
+
+10000
+";
+
+  const TWO_ELVES: &str = "1\n2\n\n3\n";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
     main:   day01,        "data/day01.txt",       [70720, 207148];
-    test1:  day01,        "data/day01_test1.txt", [24000, 45000];
+    test1:  day01,        Example(EXAMPLE),       [24000, 45000];
     main:   day01_speed,  "data/day01.txt",       [70720, 207148];
-    test1:  day01_speed,  "data/day01_test1.txt", [24000, 45000];
+    test1:  day01_speed,  Example(EXAMPLE),       [24000, 45000];
+    main:   day01_stream, "data/day01.txt",       [70720, 207148];
+    test1:  day01_stream, Example(EXAMPLE),       [24000, 45000];
+    two:    day01,        Example(TWO_ELVES),     [3, 6];
+    two:    day01_speed,  Example(TWO_ELVES),     [3, 6];
+    two:    day01_stream, Example(TWO_ELVES),     [3, 6];
   );
+
+  #[test]
+  fn report() -> Result<()> {
+    let input = Input::from_bytes("day01_test_report", EXAMPLE.as_bytes().to_vec());
+    let report = elves(&input)?;
+    assert_eq!(report.iter().map(|elf| elf.rank).collect::<Vec<_>>(), [4, 5, 2, 1, 3]);
+    assert_eq!(report[3], Elf { index: 3, items: 3, total: 24000, rank: 1 });
+    assert_eq!(top_calories(&input, 2)?, 35000);
+    assert_eq!(top_calories(&input, 10)?, 55000);
+    let input = Input::from_bytes("day01_test_report", b"5\n\n3\n\n5\n".to_vec());
+    assert_eq!(elves(&input)?.iter().map(|elf| elf.rank).collect::<Vec<_>>(), [1, 3, 1]);
+    Ok(())
+  }
 }
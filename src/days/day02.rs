@@ -1,8 +1,196 @@
 // #![allow(unused_variables)]
-use std::path::Path;
 
+use crate::input::{Input, LineReader};
+use crate::tokenizer::lines;
 use crate::Result;
-use crate::utils::ReturnType;
+use crate::utils::Solution;
+
+/// Result of a round for the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+  Lose,
+  Draw,
+  Win,
+}
+
+/// Rules of a game where both players pick a move at the same time, given as data:
+/// the moves with their score, which move beats which, the score of each outcome and the symbols of the input.
+#[derive(Debug, Clone)]
+pub struct Game {
+  moves: Vec<(String, u64)>,
+  /// beats[a][b] is true when `a` beats `b`, neither beats the other for a draw
+  beats: Vec<Vec<bool>>,
+  /// Score of a lost, drawn and won round
+  outcome_scores: [u64; 3],
+  /// Symbols of the opponent moves, of my moves (part1) and of the outcomes to reach (part2)
+  opponent_symbols: Vec<(char, usize)>,
+  my_symbols: Vec<(char, usize)>,
+  outcome_symbols: Vec<(char, Outcome)>,
+}
+
+impl Game {
+  /// Game from its moves (name, score) and its beats relation
+  pub fn new(moves: &[(&str, u64)], beats: Vec<Vec<bool>>, outcome_scores: [u64; 3]) -> Result<Self> {
+    if moves.is_empty() || beats.len() != moves.len() || beats.iter().any(|row| row.len() != moves.len()) {
+      return Err("The beats relation needs a row and a column per move".into());
+    }
+    for a in 0..moves.len() {
+      for b in 0..moves.len() {
+        if beats[a][b] && beats[b][a] {
+          return Err(format!("{} and {} beat each other", moves[a].0, moves[b].0).into());
+        }
+      }
+    }
+    Ok(Self {
+      moves: moves.iter().map(|(name, score)| (name.to_string(), *score)).collect(),
+      beats,
+      outcome_scores,
+      opponent_symbols: Vec::new(),
+      my_symbols: Vec::new(),
+      outcome_symbols: Vec::new(),
+    })
+  }
+
+  /// Balanced tournament of an odd number of moves: each move beats the (n-1)/2 moves before it (cyclically).
+  /// Move scores are their position from 1, a round scores 0, 3 or 6.
+  pub fn cyclic(names: &[&str]) -> Result<Self> {
+    let n = names.len();
+    if n.is_multiple_of(2) {
+      return Err(format!("A cyclic game needs an odd number of moves, not {}", n).into());
+    }
+    let beats = (0..n)
+      .map(|a| {
+        (0..n)
+          .map(|b| (1..=n / 2).any(|offset| (a + n - offset) % n == b))
+          .collect()
+      })
+      .collect();
+    let moves = names
+      .iter()
+      .zip(1..)
+      .map(|(name, score)| (*name, score))
+      .collect::<Vec<_>>();
+    Self::new(&moves, beats, [0, 3, 6])
+  }
+
+  /// Input symbols, the n-th symbol of `opponent` and `mine` is the n-th move, `outcomes` are lose, draw and win
+  pub fn with_symbols(mut self, opponent: &str, mine: &str, outcomes: &str) -> Result<Self> {
+    if opponent.chars().count() != self.moves.len() || mine.chars().count() != self.moves.len() {
+      return Err("One symbol per move is needed".into());
+    }
+    if outcomes.chars().count() != 3 {
+      return Err("Three outcome symbols are needed: lose, draw, win".into());
+    }
+    self.opponent_symbols = opponent.chars().zip(0..).collect();
+    self.my_symbols = mine.chars().zip(0..).collect();
+    self.outcome_symbols = outcomes
+      .chars()
+      .zip([Outcome::Lose, Outcome::Draw, Outcome::Win])
+      .collect();
+    Ok(self)
+  }
+
+  /// The puzzle: A, B, C for the opponent Rock, Paper, Scissors, X, Y, Z for mine or for lose, draw, win
+  pub fn rock_paper_scissors() -> Self {
+    Self::cyclic(&["Rock", "Paper", "Scissors"])
+      .and_then(|game| game.with_symbols("ABC", "XYZ", "XYZ"))
+      .expect("valid game")
+  }
+
+  /// Rock crushes Lizard and Scissors, Spock smashes Scissors and vaporizes Rock, ...
+  /// The order makes each move beat the two before it.
+  pub fn rock_paper_scissors_lizard_spock() -> Self {
+    Self::cyclic(&["Rock", "Spock", "Paper", "Lizard", "Scissors"])
+      .and_then(|game| game.with_symbols("ABCDE", "VWXYZ", "XYZ"))
+      .expect("valid game")
+  }
+
+  pub fn moves(&self) -> impl Iterator<Item = &str> {
+    self.moves.iter().map(|(name, _)| name.as_str())
+  }
+
+  pub fn outcome(&self, mine: usize, opponent: usize) -> Outcome {
+    if self.beats[mine][opponent] {
+      Outcome::Win
+    } else if self.beats[opponent][mine] {
+      Outcome::Lose
+    } else {
+      Outcome::Draw
+    }
+  }
+
+  pub fn score(&self, mine: usize, opponent: usize) -> u64 {
+    self.moves[mine].1 + self.outcome_scores[self.outcome(mine, opponent) as usize]
+  }
+
+  /// Best scoring move reaching `outcome` against `opponent`, None if no move does
+  pub fn respond(&self, opponent: usize, outcome: Outcome) -> Option<usize> {
+    (0..self.moves.len())
+      .filter(|mine| self.outcome(*mine, opponent) == outcome)
+      .max_by_key(|mine| (self.score(*mine, opponent), std::cmp::Reverse(*mine)))
+  }
+
+  /// Strategy optimizer: the moves giving the best total score against a known sequence of opponent moves.
+  /// Rounds are independent so the best move of each round is picked, the lowest move on ties.
+  pub fn best_strategy(&self, opponents: &[usize]) -> (u64, Vec<usize>) {
+    let best = (0..self.moves.len())
+      .map(|opponent| {
+        (0..self.moves.len())
+          .max_by_key(|mine| (self.score(*mine, opponent), std::cmp::Reverse(*mine)))
+          .unwrap_or(0)
+      })
+      .collect::<Vec<_>>();
+    let moves = opponents.iter().map(|opponent| best[*opponent]).collect::<Vec<_>>();
+    let score = opponents
+      .iter()
+      .zip(&moves)
+      .map(|(opponent, mine)| self.score(*mine, *opponent))
+      .sum();
+    (score, moves)
+  }
+
+  fn symbol<T: Copy>(symbols: &[(char, T)], token: &str) -> Result<T> {
+    let mut chars = token.chars();
+    match (chars.next(), chars.next()) {
+      (Some(c), None) => symbols
+        .iter()
+        .find(|(symbol, _)| *symbol == c)
+        .map(|(_, value)| *value)
+        .ok_or_else(|| format!("Invalid character : {}", c).into()),
+      _ => Err(format!("Invalid character : {}", token).into()),
+    }
+  }
+
+  /// Opponent moves of a strategy guide
+  pub fn opponent_moves(&self, guide: &str) -> Result<Vec<usize>> {
+    guide
+      .lines()
+      .map(|line| Self::symbol(&self.opponent_symbols, line.split(' ').next().unwrap_or_default()))
+      .collect()
+  }
+
+  /// Score of a strategy guide with the second column read as my move (part1) and as the outcome to reach (part2).
+  /// Games can have more moves than outcomes, part2 is None when a symbol of the guide isn't an outcome.
+  pub fn play(&self, guide: &str) -> Result<(u64, Option<u64>)> {
+    let mut part1 = 0;
+    let mut part2 = Some(0);
+    for line in guide.lines() {
+      let mut hands = line.split(' ');
+      let (Some(opponent), Some(second)) = (hands.next(), hands.next()) else {
+        return Err(format!("Missing hand : {}", line).into());
+      };
+      let opponent = Self::symbol(&self.opponent_symbols, opponent)?;
+      part1 += self.score(Self::symbol(&self.my_symbols, second)?, opponent);
+      if let (Some(total), Ok(outcome)) = (part2, Self::symbol(&self.outcome_symbols, second)) {
+        let mine = self.respond(opponent, outcome).ok_or("No move reaches the outcome")?;
+        part2 = Some(total + self.score(mine, opponent));
+      } else {
+        part2 = None;
+      }
+    }
+    Ok((part1, part2))
+  }
+}
 
 // A for Rock, B for Paper, and C for Scissors
 // X for Rock, Y for Paper, and Z for Scissors
@@ -11,20 +199,6 @@ trait InterpreteToInt {
   fn interprete(&self) -> Result<u64>;
 }
 
-impl InterpreteToInt for &str {
-  fn interprete(&self) -> Result<u64> {
-    match *self {
-      "A" => Ok(0),
-      "B" => Ok(1),
-      "C" => Ok(2),
-      "X" => Ok(0),
-      "Y" => Ok(1),
-      "Z" => Ok(2),
-      character => Err(format!("Invalid character : {}", character).into()),
-    }
-  }
-}
-
 impl InterpreteToInt for char {
   #[inline(always)]
   fn interprete(&self) -> Result<u64> {
@@ -55,41 +229,274 @@ fn result_part2(opponent_choice: u64, outcome: u64) -> Result<u64> {
   Ok(choice_score + outcome * 3)
 }
 
-pub fn day02(filename: &Path) -> Result<ReturnType> {
+// The puzzle is one configuration of the game engine
+pub fn day02(input: &Input) -> Result<Solution<u64, u64>> {
+  let (part1, part2) = Game::rock_paper_scissors().play(&input.read_to_string()?)?;
+  Ok(Solution::new(part1, part2.ok_or("Invalid outcome")?))
+}
+
+pub fn day02_speed(input: &Input) -> Result<Solution<u64, u64>> {
   let mut part1 = 0;
   let mut part2 = 0;
-  for line in std::fs::read_to_string(filename)?.lines() {
-    let line_str = line.split(" ").filter_map(|v| v.interprete().ok()).collect::<Vec<_>>();
-    part1 += result_part1(line_str[0], line_str[1])?;
-    part2 += result_part2(line_str[0], line_str[1])?;
+  for line in lines(&input.read_bytes()?) {
+    let value1 = (*line.first().ok_or("Can't index/Out of bound")? as char).interprete()?;
+    let value2 = (*line.get(2).ok_or("Can't index/Out of bound")? as char).interprete()?;
+    part1 += result_part1(value1, value2)?;
+    part2 += result_part2(value1, value2)?;
   }
 
-  Ok(ReturnType::Numeric(part1, part2))
+  Ok(Solution::new(part1, part2))
 }
 
-pub fn day02_speed(filename: &Path) -> Result<ReturnType> {
+// Streaming version, memory doesn't depend on input size
+pub fn day02_stream(input: &Input) -> Result<Solution<u64, u64>> {
+  let mut lines = LineReader::new(input.reader()?);
   let mut part1 = 0;
   let mut part2 = 0;
-  for line in std::fs::read_to_string(filename)?.lines() {
-    let value1 = line.chars().nth(0).expect("Can't index/Out of bound").interprete()?;
-    let value2 = line.chars().nth(2).expect("Can't index/Out of bound").interprete()?;
+  while let Some(line) = lines.next_line()? {
+    let line = line.as_bytes();
+    if line.len() < 3 {
+      return Err(format!("Line too short : {}", String::from_utf8_lossy(line)).into());
+    }
+    let value1 = (line[0] as char).interprete()?;
+    let value2 = (line[2] as char).interprete()?;
     part1 += result_part1(value1, value2)?;
     part2 += result_part2(value1, value2)?;
   }
 
-  Ok(ReturnType::Numeric(part1, part2))
+  Ok(Solution::new(part1, part2))
+}
+
+/// Scores of both parts for each (opponent, me) pair at index opponent * 3 + me,
+/// part1 in the low 32 bits and part2 in the high 32 bits so one addition updates both
+const SCORES: [u64; 9] = {
+  let mut table = [0; 9];
+  let mut index = 0;
+  while index < 9 {
+    let (opponent, me) = (index as u64 / 3, index as u64 % 3);
+    let part1 = me + 1 + (me + 4 - opponent) % 3 * 3;
+    let part2 = (opponent + me + 2) % 3 + 1 + me * 3;
+    table[index] = part1 | part2 << 32;
+    index += 1;
+  }
+  table
+};
+
+/// Records summed before unpacking, small enough for the 32 bits halves not to overflow
+const CHUNK: usize = 4096;
+
+/// Error of the first invalid record of `records`, `first_line` is the line of its first record
+fn invalid_record(records: &[u8], first_line: usize) -> Box<dyn std::error::Error> {
+  let position = records
+    .chunks(4)
+    .position(|record| !matches!(record, [b'A'..=b'C', b' ', b'X'..=b'Z', b'\n']))
+    .unwrap_or(0);
+  let record = &records[position * 4..(position * 4 + 4).min(records.len())];
+  format!(
+    "Invalid round at line {} : {:?}",
+    first_line + position + 1,
+    String::from_utf8_lossy(record)
+  )
+  .into()
+}
+
+/// Sum of the scores of complete 4 bytes records, one table lookup per record
+fn table_scalar(records: &[u8]) -> Result<(u64, u64)> {
+  let (mut part1, mut part2) = (0, 0);
+  for (index, chunk) in records.chunks(CHUNK * 4).enumerate() {
+    let mut packed = 0;
+    let mut invalid = false;
+    for record in chunk.chunks_exact(4) {
+      let opponent = record[0].wrapping_sub(b'A');
+      let me = record[2].wrapping_sub(b'X');
+      invalid |= (opponent > 2) | (me > 2) | (record[1] != b' ') | (record[3] != b'\n');
+      packed += SCORES[(opponent as usize * 3 + me as usize).min(8)];
+    }
+    // checked once per chunk, the error is rebuilt from the chunk
+    if invalid {
+      return Err(invalid_record(chunk, index * CHUNK));
+    }
+    part1 += packed & 0xFFFF_FFFF;
+    part2 += packed >> 32;
+  }
+  Ok((part1, part2))
+}
+
+/// Same sums 4 records at a time: the 9 byte scores of each part are looked up with `pshufb`
+/// and summed with `psadbw`. None when a record is invalid.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+unsafe fn table_ssse3(records: &[u8]) -> Option<(u64, u64)> {
+  use std::arch::x86_64::*;
+
+  let table = |part: fn(u64) -> u64| {
+    let mut bytes = [0u8; 16];
+    for (byte, score) in bytes.iter_mut().zip(SCORES) {
+      *byte = part(score) as u8;
+    }
+    bytes
+  };
+  let part1_table = table(|score| score & 0xFFFF_FFFF);
+  let part2_table = table(|score| score >> 32);
+  let part1_table = _mm_loadu_si128(part1_table.as_ptr() as *const __m128i);
+  let part2_table = _mm_loadu_si128(part2_table.as_ptr() as *const __m128i);
+  let bias = _mm_set1_epi32(i32::from_le_bytes([b'A', b' ', b'X', b'\n']));
+  let limit = _mm_set1_epi32(i32::from_le_bytes([2, 0, 2, 0]));
+  // opponent * 3 + me in 16 bits lanes, then the pairs of lanes added in 32 bits lanes
+  let weights = _mm_set1_epi32(i32::from_le_bytes([3, 0, 1, 0]));
+  let ones = _mm_set1_epi16(1);
+  // upper bytes of each lane at 0x80 so pshufb sets them to 0
+  let high = _mm_set1_epi32(0xFFFF_FF00u32 as i32);
+  let zero = _mm_setzero_si128();
+
+  let (mut part1, mut part2, mut invalid) = (zero, zero, zero);
+  for block in records.chunks_exact(16) {
+    let values = _mm_sub_epi8(_mm_loadu_si128(block.as_ptr() as *const __m128i), bias);
+    invalid = _mm_or_si128(invalid, _mm_subs_epu8(values, limit));
+    let index = _mm_madd_epi16(_mm_maddubs_epi16(values, weights), ones);
+    let index = _mm_or_si128(index, high);
+    part1 = _mm_add_epi64(part1, _mm_sad_epu8(_mm_shuffle_epi8(part1_table, index), zero));
+    part2 = _mm_add_epi64(part2, _mm_sad_epu8(_mm_shuffle_epi8(part2_table, index), zero));
+  }
+  if _mm_movemask_epi8(_mm_cmpeq_epi8(invalid, zero)) != 0xFFFF {
+    return None;
+  }
+  let sum = |sums: __m128i| {
+    let mut lanes = [0u64; 2];
+    _mm_storeu_si128(lanes.as_mut_ptr() as *mut __m128i, sums);
+    lanes[0] + lanes[1]
+  };
+  Some((sum(part1), sum(part2)))
+}
+
+/// Blocks of 16 bytes with SSSE3 when available, the remaining records and the errors with the scalar kernel
+fn table_sums(records: &[u8]) -> Result<(u64, u64)> {
+  #[cfg(target_arch = "x86_64")]
+  if is_x86_feature_detected!("ssse3") {
+    let blocks = records.len() / 16 * 16;
+    // SAFETY: ssse3 is available
+    if let Some((part1, part2)) = unsafe { table_ssse3(&records[..blocks]) } {
+      let (rest1, rest2) = table_scalar(&records[blocks..]).map_err(|_| invalid_record(records, 0))?;
+      return Ok((part1 + rest1, part2 + rest2));
+    }
+  }
+  table_scalar(records)
+}
+
+// Input as fixed 4 bytes records "A X\n", both parts from one lookup table, no parsing at all
+pub fn day02_table(input: &Input) -> Result<Solution<u64, u64>> {
+  let bytes = input.read_bytes()?;
+  let complete = bytes.len() / 4 * 4;
+  let (mut part1, mut part2) = table_sums(&bytes[..complete])?;
+  match &bytes[complete..] {
+    [] => {}
+    // last round without its newline
+    [opponent, b' ', me] => {
+      let (part1_last, part2_last) = table_scalar(&[*opponent, b' ', *me, b'\n']).map_err(|_| {
+        format!(
+          "Invalid round at line {} : {:?}",
+          complete / 4 + 1,
+          String::from_utf8_lossy(&bytes[complete..])
+        )
+      })?;
+      part1 += part1_last;
+      part2 += part2_last;
+    }
+    _ => return Err("Rounds of 4 bytes expected (\"A X\\n\")".into()),
+  }
+
+  Ok(Solution::new(part1, part2))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"A Y
+B X
+C Z
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
     main:   day02,        "data/day02.txt",       [11475, 16862];
-    test1:  day02,        "data/day02_test1.txt", [15, 12];
+    test1:  day02,        Example(EXAMPLE),       [15, 12];
     main:   day02_speed,  "data/day02.txt",       [11475, 16862];
-    test1:  day02_speed,  "data/day02_test1.txt", [15, 12];
+    test1:  day02_speed,  Example(EXAMPLE),       [15, 12];
+    main:   day02_stream, "data/day02.txt",       [11475, 16862];
+    test1:  day02_stream, Example(EXAMPLE),       [15, 12];
+    main:   day02_table,  "data/day02.txt",       [11475, 16862];
+    test1:  day02_table,  Example(EXAMPLE),       [15, 12];
   );
+
+  #[test]
+  fn rock_paper_scissors_lizard_spock() -> Result<()> {
+    let game = Game::rock_paper_scissors_lizard_spock();
+    let index = |name: &str| game.moves().position(|other| other == name).unwrap();
+    for (winner, loser) in [
+      ("Scissors", "Paper"),
+      ("Paper", "Rock"),
+      ("Rock", "Lizard"),
+      ("Lizard", "Spock"),
+      ("Spock", "Scissors"),
+      ("Scissors", "Lizard"),
+      ("Lizard", "Paper"),
+      ("Paper", "Spock"),
+      ("Spock", "Rock"),
+      ("Rock", "Scissors"),
+    ] {
+      assert_eq!(game.outcome(index(winner), index(loser)), Outcome::Win);
+      assert_eq!(game.outcome(index(loser), index(winner)), Outcome::Lose);
+    }
+    // part1: Paper (X) against Rock (A) 3 + 6, Scissors (Z) against Scissors (E) 5 + 3
+    // part2: Scissors loses against Rock 5 + 0, Spock beats Scissors 2 + 6 (better than Rock 1 + 6)
+    assert_eq!(game.play("A X\nE Z\n")?, (17, Some(13)));
+    // V and W are moves but not outcomes: Rock (V) against Rock 1 + 3, Spock (W) against Paper (C) 2 + 0, then 5 + 3
+    assert_eq!(game.play("A V\nC W\nE Z\n")?, (4 + 2 + 8, None));
+    Ok(())
+  }
+
+  #[test]
+  fn engine_rules() {
+    assert!(Game::cyclic(&["a", "b", "c", "d"]).is_err());
+    assert!(Game::new(
+      &[("a", 1), ("b", 2)],
+      vec![vec![false, true], vec![true, false]],
+      [0, 3, 6]
+    )
+    .is_err());
+    let game = Game::rock_paper_scissors();
+    // Paper against Rock, Scissors against Paper, Rock against Scissors (1 + 6 beats Scissors 3 + 3)
+    assert_eq!(game.best_strategy(&[0, 1, 2]), (8 + 9 + 7, vec![1, 2, 0]));
+    let nine = Game::cyclic(&["0", "1", "2", "3", "4", "5", "6", "7", "8"]).unwrap();
+    assert!((0..9).all(|a| (0..9).filter(|b| nine.outcome(a, *b) == Outcome::Win).count() == 4));
+  }
+
+  #[test]
+  fn table_kernels() -> Result<()> {
+    let content = crate::generate::generate(2, &crate::generate::Options::default())?;
+    let expected = day02_speed(&Input::from_bytes("generated", content.clone().into_bytes()))?;
+    // every remainder after the 16 bytes blocks, with and without the last newline
+    for rounds in [2500, 2499, 2498, 2497] {
+      let prefix = &content[..rounds * 4];
+      let expected = day02_speed(&Input::from_bytes("generated", prefix.as_bytes().to_vec()))?;
+      for records in [prefix, prefix.trim_end()] {
+        let input = Input::from_bytes("generated", records.as_bytes().to_vec());
+        assert_eq!(day02_table(&input)?, expected);
+      }
+    }
+    assert_eq!(table_scalar(content.as_bytes())?, table_sums(content.as_bytes())?);
+    assert_eq!(
+      day02_table(&Input::from_bytes("generated", content.into_bytes()))?,
+      expected
+    );
+    let error = |content: &str| day02_table(&Input::from_bytes("bad", content.as_bytes().to_vec())).unwrap_err();
+    let mut rounds = "A X\n".repeat(40);
+    rounds.replace_range(33 * 4..33 * 4 + 3, "D Y");
+    assert_eq!(error(&rounds).to_string(), "Invalid round at line 34 : \"D Y\\n\"");
+    assert_eq!(error("A X\nB Q").to_string(), "Invalid round at line 2 : \"B Q\"");
+    assert!(error("A X\nB").to_string().contains("4 bytes"));
+    Ok(())
+  }
 }
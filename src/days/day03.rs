@@ -1,55 +1,69 @@
 // #![allow(unused_variables)]
-use std::path::Path;
 
+use crate::input::Input;
 use crate::Result;
-use crate::utils::ReturnType;
+use crate::utils::Solution;
 
-fn to_priority(item: char) -> u32 {
-  if item.is_lowercase() {
-    item as u32 - 'a' as u32 + 1
-  } else {
-    item as u32 - 'A' as u32 + 27
+fn to_priority(item: char) -> Result<u64> {
+  match item {
+    'a'..='z' => Ok((item as u32 - 'a' as u32 + 1) as u64),
+    'A'..='Z' => Ok((item as u32 - 'A' as u32 + 27) as u64),
+    _ => Err(format!("Invalid item {:?}", item).into()),
   }
 }
 
-pub fn day03(filename: &Path) -> Result<ReturnType> {
-  let file_content = std::fs::read_to_string(filename)?;
+// Items are ascii letters, it also makes the split in compartments safe
+fn check_rucksack(line: &str) -> Result<&str> {
+  if !line.is_ascii() {
+    return Err(format!("Invalid rucksack {:?}", line).into());
+  }
+  Ok(line)
+}
+
+pub fn day03(input: &Input) -> Result<Solution<u64, u64>> {
+  let file_content = input.read_to_string()?;
   let part1 = file_content
     .lines()
     .map(|line| {
+      let line = check_rucksack(line)?;
       let compartments_size = line.len() / 2;
       let compartments1 = &line[..compartments_size];
       let compartments2 = &line[compartments_size..];
       for elem1 in compartments1.chars() {
         if compartments2.contains(elem1) {
-          return to_priority(elem1) as u64;
+          return to_priority(elem1);
         };
       }
-      0
+      Ok(0)
     })
-    .sum();
+    .sum::<Result<u64>>()?;
   let part2 = file_content
     .lines()
     .collect::<Vec<_>>()
     .chunks(3)
     .map(|elfs_group| {
+      if elfs_group.len() != 3 {
+        return Err("Incomplete group of elves".into());
+      }
       for elem1 in elfs_group[0].chars() {
         if elfs_group[1].contains(elem1) && elfs_group[2].contains(elem1) {
-          return to_priority(elem1) as u64;
+          return to_priority(elem1);
         };
       }
-      0
+      Ok(0)
     })
-    .sum();
+    .sum::<Result<u64>>()?;
 
-    Ok(ReturnType::Numeric(part1, part2))
+    Ok(Solution::new(part1, part2))
   }
 
-pub fn day03_speed(filename: &Path) -> Result<ReturnType> {
+pub fn day03_speed(input: &Input) -> Result<Solution<u64, u64>> {
   let mut chunk = Vec::new();
   let mut part1 = 0;
   let mut part2 = 0;
-  for line in std::fs::read_to_string(filename)?.lines() {
+  let content = input.read_to_string()?;
+  for line in content.lines() {
+    let line = check_rucksack(line)?;
     // part1
     let compartments_size = line.len() / 2;
     let compartments1 = &line[..compartments_size];
@@ -57,7 +71,7 @@ pub fn day03_speed(filename: &Path) -> Result<ReturnType> {
     part1 += 'part1: {
       for elem1 in compartments1.chars() {
         if compartments2.contains(elem1) {
-          break 'part1 to_priority(elem1) as u64;
+          break 'part1 to_priority(elem1)?;
         };
       }
       0
@@ -68,7 +82,7 @@ pub fn day03_speed(filename: &Path) -> Result<ReturnType> {
       part2 += 'part2: {
         for elem1 in chunk[0].chars() {
           if chunk[1].contains(elem1) && chunk[2].contains(elem1) {
-            break 'part2 to_priority(elem1) as u64;
+            break 'part2 to_priority(elem1)?;
           };
         }
         0
@@ -76,20 +90,139 @@ pub fn day03_speed(filename: &Path) -> Result<ReturnType> {
       chunk.clear();
     }
   }
+  if !chunk.is_empty() {
+    return Err("Incomplete group of elves".into());
+  }
+
+  Ok(Solution::new(part1, part2))
+}
+
+/// Items of a set of compartments as bits, the bit of an item is its priority (1 to 52)
+fn items_mask(items: &[u8]) -> Result<u64> {
+  items.iter().try_fold(0, |mask, item| match item {
+    b'a'..=b'z' => Ok(mask | 1 << (item - b'a' + 1)),
+    b'A'..=b'Z' => Ok(mask | 1 << (item - b'A' + 27)),
+    _ => Err(format!("Invalid item {:?}", *item as char).into()),
+  })
+}
+
+/// Priority of the lowest item of a mask, 0 if empty
+fn lowest_priority(mask: u64) -> u64 {
+  if mask == 0 {
+    0
+  } else {
+    mask.trailing_zeros() as u64
+  }
+}
+
+/// How rucksacks are packed: number of compartments of a rucksack and of rucksacks in a group of elves.
+/// Each set of items is a mask, the common items are the AND of the masks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Packing {
+  pub compartments: usize,
+  pub group_size: usize,
+}
+
+impl Default for Packing {
+  /// Puzzle packing, 2 compartments and groups of 3 elves
+  fn default() -> Self {
+    Self {
+      compartments: 2,
+      group_size: 3,
+    }
+  }
+}
+
+impl Packing {
+  /// Priority of the item in every compartment of a rucksack, 0 if none
+  pub fn misplaced(&self, rucksack: &[u8]) -> Result<u64> {
+    if self.compartments == 0 || !rucksack.len().is_multiple_of(self.compartments) {
+      let rucksack = String::from_utf8_lossy(rucksack);
+      return Err(
+        format!(
+          "Rucksack {:?} can't be split in {} compartments",
+          rucksack, self.compartments
+        )
+        .into(),
+      );
+    }
+    let common = rucksack
+      .chunks((rucksack.len() / self.compartments).max(1))
+      .map(items_mask)
+      .try_fold(u64::MAX, |common, mask| mask.map(|mask| common & mask))?;
+    // an empty rucksack has no compartment and keeps u64::MAX, bit 0 is no item so it's priority 0 too
+    Ok(lowest_priority(common))
+  }
+
+  /// Priority of the badge, the item in every rucksack of a group, 0 if none
+  pub fn badge(&self, group: &[&[u8]]) -> Result<u64> {
+    if group.len() != self.group_size {
+      return Err("Incomplete group of elves".into());
+    }
+    let common = group
+      .iter()
+      .map(|rucksack| items_mask(rucksack))
+      .try_fold(u64::MAX, |common, mask| mask.map(|mask| common & mask))?;
+    Ok(lowest_priority(common))
+  }
+
+  pub fn solve(&self, content: &str) -> Result<Solution<u64, u64>> {
+    if self.group_size == 0 {
+      return Err("Groups of elves can't be empty".into());
+    }
+    let rucksacks = content.lines().map(str::as_bytes).collect::<Vec<_>>();
+    let part1 = rucksacks
+      .iter()
+      .map(|rucksack| self.misplaced(rucksack))
+      .sum::<Result<u64>>()?;
+    let part2 = rucksacks
+      .chunks(self.group_size)
+      .map(|group| self.badge(group))
+      .sum::<Result<u64>>()?;
+    Ok(Solution::new(part1, part2))
+  }
+}
 
-  Ok(ReturnType::Numeric(part1, part2))
+// Items as bits of a u64, common items with AND instead of searching each item
+pub fn day03_bitset(input: &Input) -> Result<Solution<u64, u64>> {
+  Packing::default().solve(&input.read_to_string()?)
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
     main:   day03, "data/day03.txt",                  [8202, 2864];
-    test1:  day03, "data/day03_test1.txt",            [157, 70];
+    test1:  day03, Example(EXAMPLE),                  [157, 70];
     main:   day03_speed, "data/day03.txt",            [8202, 2864];
-    test1:  day03_speed, "data/day03_test1.txt",      [157, 70];
+    test1:  day03_speed, Example(EXAMPLE),            [157, 70];
+    main:   day03_bitset, "data/day03.txt",           [8202, 2864];
+    test1:  day03_bitset, Example(EXAMPLE),           [157, 70];
   );
+
+  #[test]
+  fn configurable_packing() -> Result<()> {
+    let packing = Packing {
+      compartments: 3,
+      group_size: 2,
+    };
+    // c in the 3 compartments of the first rucksack, Z in the 3 of the second, b in both rucksacks
+    assert_eq!(packing.solve("abcdbcecf\nZbyZxyZxw\n")?, Solution::new(3 + 52, 2));
+    assert!(packing.solve("abcd\n").is_err());
+    assert!(packing.solve("abc\n").unwrap_err().to_string().contains("Incomplete"));
+    assert!(Packing::default().solve("a1\n").is_err());
+    assert_eq!(Packing::default().misplaced(b"")?, 0);
+    Ok(())
+  }
 }
@@ -1,58 +1,172 @@
 // #![allow(unused_variables)]
-use std::path::Path;
 
+use crate::input::{Input, LineReader};
+use crate::tokenizer::{lines, Tokenizer};
 use crate::Result;
-use crate::utils::ReturnType;
-
-pub fn day04(filename: &Path) -> Result<ReturnType> {
-  let file_content = std::fs::read_to_string(filename)?;
-  let part1 = file_content
-    .lines()
-    .map(|line| {
-      let indexes: Vec<&str> = line.split(&['-', ',']).collect();
-      let min_elf1 = indexes[0].parse::<u32>().unwrap_or(0);
-      let max_elf1 = indexes[1].parse::<u32>().unwrap_or(0);
-      let min_elf2 = indexes[2].parse::<u32>().unwrap_or(0);
-      let max_elf2 = indexes[3].parse::<u32>().unwrap_or(0);
-      if min_elf1 <= min_elf2 && max_elf1 >= max_elf2 {
-        1
-      } else if min_elf2 <= min_elf1 && max_elf2 >= max_elf1 {
-        1
-      } else {
-        0
-      }
-    })
-    .sum();
-  let part2 = file_content
-    .lines()
-    .map(|line| {
-      let indexes: Vec<&str> = line.split(&['-', ',']).collect();
-      let min_elf1 = indexes[0].parse::<u32>().unwrap_or(0);
-      let max_elf1 = indexes[1].parse::<u32>().unwrap_or(0);
-      let min_elf2 = indexes[2].parse::<u32>().unwrap_or(0);
-      let max_elf2 = indexes[3].parse::<u32>().unwrap_or(0);
-      if min_elf1 <= min_elf2 && max_elf1 >= min_elf2 {
-        1
-      } else if min_elf2 <= min_elf1 && max_elf2 >= min_elf1 {
-        1
-      } else {
-        0
-      }
-    })
-    .sum();
-
-  Ok(ReturnType::Numeric(part1, part2))
+use crate::utils::Solution;
+
+/// The 13 relations between two intervals of Allen's interval algebra, `first` relation `second`.
+/// Intervals are half-open `[start, end)` and not empty, two intervals meet when one ends where the other starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Relation {
+  Before,
+  Meets,
+  Overlaps,
+  Starts,
+  During,
+  Finishes,
+  Equals,
+  FinishedBy,
+  Contains,
+  StartedBy,
+  OverlappedBy,
+  MetBy,
+  After,
+}
+
+impl Relation {
+  pub const ALL: [Relation; 13] = [
+    Relation::Before,
+    Relation::Meets,
+    Relation::Overlaps,
+    Relation::Starts,
+    Relation::During,
+    Relation::Finishes,
+    Relation::Equals,
+    Relation::FinishedBy,
+    Relation::Contains,
+    Relation::StartedBy,
+    Relation::OverlappedBy,
+    Relation::MetBy,
+    Relation::After,
+  ];
+
+  /// Relation of the non empty half-open intervals `first` and `second`
+  /// # Example
+  /// ```
+  /// use aoc_2022::days::day04::Relation;
+  ///
+  /// assert_eq!(Relation::between((1, 3), (3, 5)), Relation::Meets);
+  /// assert_eq!(Relation::between((2, 3), (1, 5)), Relation::During);
+  /// ```
+  pub fn between<T: Ord>(first: (T, T), second: (T, T)) -> Relation {
+    use std::cmp::Ordering::*;
+    let ((start1, end1), (start2, end2)) = (first, second);
+    match (start1.cmp(&start2), end1.cmp(&end2)) {
+      (Equal, Equal) => Relation::Equals,
+      (Equal, Less) => Relation::Starts,
+      (Equal, Greater) => Relation::StartedBy,
+      (Greater, Equal) => Relation::Finishes,
+      (Less, Equal) => Relation::FinishedBy,
+      (Greater, Less) => Relation::During,
+      (Less, Greater) => Relation::Contains,
+      (Less, Less) => match end1.cmp(&start2) {
+        Less => Relation::Before,
+        Equal => Relation::Meets,
+        Greater => Relation::Overlaps,
+      },
+      (Greater, Greater) => match start1.cmp(&end2) {
+        Greater => Relation::After,
+        Equal => Relation::MetBy,
+        Less => Relation::OverlappedBy,
+      },
+    }
+  }
+
+  /// Relation of `second` to `first`
+  pub fn inverse(self) -> Relation {
+    Relation::ALL[Relation::ALL.len() - 1 - self as usize]
+  }
+
+  /// One interval is inside the other (puzzle part1)
+  pub fn is_containment(self) -> bool {
+    matches!(
+      self,
+      Relation::Starts
+        | Relation::During
+        | Relation::Finishes
+        | Relation::Equals
+        | Relation::FinishedBy
+        | Relation::Contains
+        | Relation::StartedBy
+    )
+  }
+
+  /// Both intervals have a part in common (puzzle part2)
+  pub fn intersects(self) -> bool {
+    !matches!(
+      self,
+      Relation::Before | Relation::Meets | Relation::MetBy | Relation::After
+    )
+  }
+
+  pub fn name(self) -> &'static str {
+    match self {
+      Relation::Before => "before",
+      Relation::Meets => "meets",
+      Relation::Overlaps => "overlaps",
+      Relation::Starts => "starts",
+      Relation::During => "during",
+      Relation::Finishes => "finishes",
+      Relation::Equals => "equals",
+      Relation::FinishedBy => "finished by",
+      Relation::Contains => "contains",
+      Relation::StartedBy => "started by",
+      Relation::OverlappedBy => "overlapped by",
+      Relation::MetBy => "met by",
+      Relation::After => "after",
+    }
+  }
+}
+
+/// Sections `start-end` of an elf (both included) as the half-open interval `[start, end + 1)`
+fn parse_sections(range: &str) -> Result<(u64, u64)> {
+  let (start, end) = range
+    .split_once('-')
+    .ok_or_else(|| format!("Invalid range {:?}", range))?;
+  let (start, end) = (start.parse::<u64>()?, end.parse::<u64>()?);
+  if start > end {
+    return Err(format!("Invalid range {:?}, the start is after the end", range).into());
+  }
+  Ok((start, end.checked_add(1).ok_or("Section out of range")?))
+}
+
+/// Number of pairs of elves in each relation, in the order of `Relation::ALL`
+pub fn relations(input: &Input) -> Result<[u64; 13]> {
+  let mut histogram = [0; 13];
+  for line in input.read_to_string()?.lines() {
+    let (elf1, elf2) = line.split_once(',').ok_or_else(|| format!("Invalid pair {:?}", line))?;
+    histogram[Relation::between(parse_sections(elf1)?, parse_sections(elf2)?) as usize] += 1;
+  }
+  Ok(histogram)
+}
+
+// Both parts are sums of the histogram of relations
+pub fn day04(input: &Input) -> Result<Solution<u64, u64>> {
+  let histogram = relations(input)?;
+  let count = |selected: fn(Relation) -> bool| {
+    Relation::ALL
+      .iter()
+      .filter(|relation| selected(**relation))
+      .map(|relation| histogram[*relation as usize])
+      .sum::<u64>()
+  };
+
+  Ok(Solution::new(
+    count(Relation::is_containment),
+    count(Relation::intersects),
+  ))
 }
 
-pub fn day04_speed(filename: &Path) -> Result<ReturnType> {
+pub fn day04_speed(input: &Input) -> Result<Solution<u64, u64>> {
   let mut part1 = 0;
   let mut part2 = 0;
-  for line in std::fs::read_to_string(filename)?.lines() {
-    let mut iter = line.split(&['-', ',']);
-    let min_elf1 = iter.next().ok_or("Not enough value")?.parse::<u64>()?;
-    let max_elf1 = iter.next().ok_or("Not enough value")?.parse::<u64>()?;
-    let min_elf2 = iter.next().ok_or("Not enough value")?.parse::<u64>()?;
-    let max_elf2 = iter.next().ok_or("Not enough value")?.parse::<u64>()?;
+  for line in lines(&input.read_bytes()?) {
+    let mut tokens = Tokenizer::new(line);
+    let min_elf1 = tokens.next_u64().ok_or("Not enough value")?;
+    let max_elf1 = tokens.next_u64().ok_or("Not enough value")?;
+    let min_elf2 = tokens.next_u64().ok_or("Not enough value")?;
+    let max_elf2 = tokens.next_u64().ok_or("Not enough value")?;
     // part1
     part1 += if min_elf1 <= min_elf2 && max_elf1 >= max_elf2 {
       1
@@ -71,19 +185,81 @@ pub fn day04_speed(filename: &Path) -> Result<ReturnType> {
     }
   }
 
-  Ok(ReturnType::Numeric(part1, part2))
+  Ok(Solution::new(part1, part2))
+}
+
+// Streaming version, memory doesn't depend on input size
+pub fn day04_stream(input: &Input) -> Result<Solution<u64, u64>> {
+  let mut lines = LineReader::new(input.reader()?);
+  let mut part1 = 0;
+  let mut part2 = 0;
+  while let Some(line) = lines.next_line()? {
+    let mut iter = line.split(&['-', ',']);
+    let min_elf1 = iter.next().ok_or("Not enough value")?.parse::<u64>()?;
+    let max_elf1 = iter.next().ok_or("Not enough value")?.parse::<u64>()?;
+    let min_elf2 = iter.next().ok_or("Not enough value")?.parse::<u64>()?;
+    let max_elf2 = iter.next().ok_or("Not enough value")?.parse::<u64>()?;
+    // part1
+    let fully_contains = (min_elf1 <= min_elf2 && max_elf1 >= max_elf2) || (min_elf2 <= min_elf1 && max_elf2 >= max_elf1);
+    part1 += fully_contains as u64;
+    // part2
+    let overlaps = (min_elf1 <= min_elf2 && max_elf1 >= min_elf2) || (min_elf2 <= min_elf1 && max_elf2 >= min_elf1);
+    part2 += overlaps as u64;
+  }
+
+  Ok(Solution::new(part1, part2))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
     main:   day04, "data/day04.txt",              [490, 921];
-    test1:  day04, "data/day04_test1.txt",        [2, 4];
+    test1:  day04, Example(EXAMPLE),              [2, 4];
     main:   day04_speed, "data/day04.txt",        [490, 921];
-    test1:  day04_speed, "data/day04_test1.txt",  [2, 4];
+    test1:  day04_speed, Example(EXAMPLE),        [2, 4];
+    main:   day04_stream, "data/day04.txt",       [490, 921];
+    test1:  day04_stream, Example(EXAMPLE),       [2, 4];
   );
+
+  #[test]
+  fn allen_relations() -> Result<()> {
+    // one pair per relation, in the order of Relation::ALL
+    let pairs = [
+      ((1, 2), (3, 4)),
+      ((1, 3), (3, 4)),
+      ((1, 3), (2, 4)),
+      ((1, 2), (1, 4)),
+      ((2, 3), (1, 4)),
+      ((3, 4), (1, 4)),
+      ((1, 4), (1, 4)),
+      ((1, 4), (3, 4)),
+      ((1, 4), (2, 3)),
+      ((1, 4), (1, 2)),
+      ((2, 4), (1, 3)),
+      ((3, 4), (1, 3)),
+      ((3, 4), (1, 2)),
+    ];
+    for (pair, expected) in pairs.iter().zip(Relation::ALL) {
+      assert_eq!(Relation::between(pair.0, pair.1), expected, "{:?}", pair);
+      assert_eq!(Relation::between(pair.1, pair.0), expected.inverse(), "{:?}", pair);
+    }
+    // 2-4,6-8 before, 2-3,4-5 meets, 5-7,7-9 overlaps, 2-8,3-7 contains, 6-6,4-6 finishes, 2-6,4-8 overlaps
+    let histogram = relations(&Input::from_bytes("example", EXAMPLE.as_bytes().to_vec()))?;
+    assert_eq!(histogram, [1, 1, 2, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0]);
+    let error = |content: &str| day04(&Input::from_bytes("bad", content.as_bytes().to_vec())).is_err();
+    assert!(error("2-4,6-x\n") && error("4-2,6-8\n") && error("2-4\n"));
+    Ok(())
+  }
 }
@@ -1,175 +1,600 @@
 // #![allow(unused_variables)]
 use regex::Regex;
-use std::path::Path;
 
-use crate::utils::ReturnType;
+use crate::input::Input;
+use crate::utils::Solution;
+use crate::tokenizer::Tokenizer;
 use crate::Result;
 
-enum State {
-  ParseHeader,
-  InterpreteHeader,
-  ParseMovement,
-}
-
-pub fn day05(filename: &Path) -> Result<ReturnType> {
-  let file_content = std::fs::read_to_string(filename)?;
-  let content_iterator = file_content.lines();
-  let mut header = Vec::new();
-  let mut bin_size = 0;
-  let mut state = State::ParseHeader;
-  let regex = Regex::new(r"move (\d+) from (\d+) to (\d+)")?;
-  let mut board_part1: Vec<Vec<char>> = Vec::new();
-  let mut board_part2: Vec<Vec<char>> = Vec::new();
-  // Use a state machine because I was not able to make iterator continue during hot time
-  // but lucky enough the switch between state allow to skip the empty line between header and movement
-  for line in content_iterator {
-    match state {
-      State::ParseHeader => {
-        // Parse header
-        if line.chars().nth(1).unwrap_or('0') == '1' {
-          bin_size = line
-            .chars()
-            .rev()
-            .nth(0)
-            .ok_or("Empty line")?
-            .to_digit(10)
-            .ok_or("Fail to parse digit")?;
-          state = State::InterpreteHeader;
-        } else {
-          header.push(line);
-        }
-      }
-      State::InterpreteHeader => {
-        // Interprete header
-        board_part1 = vec![Vec::new(); bin_size as usize];
-        for header_line in header.iter().rev() {
-          for (index, value) in header_line.chars().skip(1).step_by(4).enumerate() {
-            if value != ' ' {
-              board_part1[index].push(value);
-            }
-          }
-        }
-        board_part2 = board_part1.clone();
-        state = State::ParseMovement;
+/// Columns (0-based, in chars) of the words of a line with their text
+fn words(line: &str) -> Vec<(usize, usize, &str)> {
+  let mut words = Vec::new();
+  let mut start = None;
+  let chars = line.char_indices().chain(std::iter::once((line.len(), ' ')));
+  for (column, (offset, c)) in chars.enumerate() {
+    match (start, c.is_whitespace()) {
+      (None, false) => start = Some((column, offset)),
+      (Some((first_column, first_offset)), true) => {
+        words.push((first_column, column - 1, &line[first_offset..offset]));
+        start = None;
       }
-      State::ParseMovement => {
-        // Parse and apply movement
-        let cap = regex.captures(line).ok_or("Fail to capture")?;
-        let quantity = cap.get(1).ok_or("Fail to capture 1")?.as_str().parse::<u64>()?;
-        let src = cap.get(2).ok_or("Fail to capture 2")?.as_str().parse::<u64>()? - 1;
-        let dst = cap.get(3).ok_or("Fail to capture 3")?.as_str().parse::<u64>()? - 1;
-        let mut temp_part1 = Vec::new();
-        let mut temp_part2 = Vec::new();
-        for _ in 0..quantity {
-          temp_part1.push(board_part1[src as usize].pop().ok_or("No enough value to pop")?);
-          temp_part2.push(board_part2[src as usize].pop().ok_or("No enough value to pop")?);
-        }
-        for elem in temp_part1 {
-          board_part1[dst as usize].push(elem);
-        }
-        for elem in temp_part2.into_iter().rev() {
-          board_part2[dst as usize].push(elem);
+      _ => {}
+    }
+  }
+  words
+}
+
+/// The index line only has stack ids
+fn is_index_line(line: &str) -> bool {
+  let mut ids = line.split_whitespace().peekable();
+  ids.peek().is_some() && ids.all(|id| id.parse::<u64>().is_ok())
+}
+
+/// Crates of the diagram, a crate is any label between brackets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stacks<'a> {
+  /// Ids of the index line, in the order of the stacks
+  pub ids: Vec<u64>,
+  /// Crate labels of each stack, bottom first
+  pub crates: Vec<Vec<&'a str>>,
+}
+
+impl<'a> Stacks<'a> {
+  /// Stacks from the crate lines (top first) and the index line.
+  /// A crate belongs to the stack whose id is under it, whatever the spacing, ids can have several digits.
+  pub fn parse(crate_lines: &[&'a str], index_line: &str) -> Result<Self> {
+    let columns = words(index_line)
+      .into_iter()
+      .map(|(first, last, id)| Ok((first, last, id.parse::<u64>()?)))
+      .collect::<Result<Vec<_>>>()?;
+    let ids = columns.iter().map(|(_, _, id)| *id).collect::<Vec<_>>();
+    if let Some(id) = ids
+      .iter()
+      .enumerate()
+      .find_map(|(index, id)| ids[..index].contains(id).then_some(id))
+    {
+      return Err(format!("Stack {} appears twice on the index line", id).into());
+    }
+    let mut crates = vec![Vec::new(); ids.len()];
+    let lines = crate_lines.iter().rev().filter(|line| !line.trim().is_empty());
+    for (level, line) in lines.enumerate() {
+      for (first, last, word) in words(line) {
+        let label = word
+          .strip_prefix('[')
+          .and_then(|word| word.strip_suffix(']'))
+          .filter(|label| !label.is_empty())
+          .ok_or_else(|| format!("Invalid crate {:?} in {:?}", word, line))?;
+        let mut below = columns
+          .iter()
+          .enumerate()
+          .filter(|(_, (start, end, _))| first <= *end && *start <= last);
+        let index = match (below.next(), below.next()) {
+          (Some((index, _)), None) => index,
+          (None, _) => return Err(format!("Crate {} isn't above a stack id", word).into()),
+          (Some(_), Some(_)) => return Err(format!("Crate {} is above several stack ids", word).into()),
+        };
+        // a crate is on top of the crates of the lines below
+        if crates[index].len() != level {
+          return Err(format!("Crate {} of stack {} isn't on top of a crate", word, ids[index]).into());
         }
+        crates[index].push(label);
       }
     }
+    Ok(Self { ids, crates })
+  }
+
+  /// Index of a stack from its id in a move
+  pub fn index(&self, id: u64) -> Result<usize> {
+    // ids are usually 1 to n
+    match self.ids.get((id as usize).wrapping_sub(1)) {
+      Some(other) if *other == id => Ok(id as usize - 1),
+      _ => self
+        .ids
+        .iter()
+        .position(|other| *other == id)
+        .ok_or_else(|| format!("Invalid stack {}", id).into()),
+    }
+  }
+
+  /// Move `quantity` crates from the top of `src` to `dst` with lifts of at most `capacity` crates,
+  /// the crates of a lift keep their order. Returns the number of lifts.
+  pub fn lift(&mut self, quantity: usize, src: usize, dst: usize, capacity: usize) -> Result<usize> {
+    let height = self.crates[src].len();
+    if quantity > height {
+      return Err(
+        format!(
+          "Can't move {} crates from stack {} of {} crates",
+          quantity, self.ids[src], height
+        )
+        .into(),
+      );
+    }
+    if capacity == 0 {
+      return Err("A crane lifts at least one crate".into());
+    }
+    let lifts = quantity.div_ceil(capacity);
+    // lifting crates to put them back on the same stack changes nothing
+    if src == dst {
+      return Ok(lifts);
+    }
+    let (from, to) = if src < dst {
+      let (low, high) = self.crates.split_at_mut(dst);
+      (&mut low[src], &mut high[0])
+    } else {
+      let (low, high) = self.crates.split_at_mut(src);
+      (&mut high[0], &mut low[dst])
+    };
+    let mut remaining = quantity;
+    while remaining > 0 {
+      let lifted = remaining.min(capacity);
+      to.extend(from.drain(from.len() - lifted..));
+      remaining -= lifted;
+    }
+    Ok(lifts)
+  }
+
+  /// Stacks drawn like the puzzle diagram, columns are widened for long labels and ids
+  /// # Example
+  /// ```
+  /// use aoc_2022::days::day05::Stacks;
+  ///
+  /// let stacks = Stacks::parse(&["    [D]    ", "[N] [C]    ", "[Z] [M] [P]"], " 1   2   3")?;
+  /// assert_eq!(stacks.render(), "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3\n");
+  /// # Ok::<(), Box<dyn std::error::Error>>(())
+  /// ```
+  pub fn render(&self) -> String {
+    let labels = self.crates.iter().flatten().map(|label| label.chars().count() + 2);
+    let ids = self.ids.iter().map(|id| id.to_string().len());
+    let width = labels.chain(ids).max().unwrap_or(3).max(3);
+    let height = self.crates.iter().map(Vec::len).max().unwrap_or(0);
+    let mut out = String::new();
+    for level in (0..height).rev() {
+      let line = self
+        .crates
+        .iter()
+        .map(|stack| match stack.get(level) {
+          Some(label) => format!("{:^width$}", format!("[{}]", label), width = width),
+          None => " ".repeat(width),
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+      out.push_str(&line);
+      out.push('\n');
+    }
+    let ids = self
+      .ids
+      .iter()
+      .map(|id| format!("{:^width$}", id, width = width))
+      .collect::<Vec<_>>()
+      .join(" ");
+    out.push_str(ids.trim_end());
+    out.push('\n');
+    out
+  }
+
+  /// One line per stack that differs from `other`, with the crates of both, bottom first
+  pub fn diff(&self, other: &Stacks) -> String {
+    let crates = |stack: &[&str]| {
+      stack
+        .iter()
+        .map(|label| format!("[{}]", label))
+        .collect::<Vec<_>>()
+        .join(" ")
+    };
+    self
+      .crates
+      .iter()
+      .zip(&other.crates)
+      .zip(&self.ids)
+      .filter(|((before, after), _)| before != after)
+      .map(|((before, after), id)| format!("stack {}: {} -> {}\n", id, crates(before), crates(after)))
+      .collect()
+  }
+
+  /// Labels of the crates on top of each stack
+  pub fn tops(&self) -> Result<String> {
+    self
+      .crates
+      .iter()
+      .zip(&self.ids)
+      .map(|(stack, id)| {
+        stack
+          .last()
+          .copied()
+          .ok_or_else(|| format!("Stack {} is empty at the end", id).into())
+      })
+      .collect()
+  }
+}
+
+/// Diagram at the top of the input, the remaining lines are the moves
+fn parse_diagram<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Result<Stacks<'a>> {
+  let mut crate_lines = Vec::new();
+  for line in lines {
+    if is_index_line(line) {
+      return Stacks::parse(&crate_lines, line);
+    }
+    crate_lines.push(line);
   }
-  let part1 = board_part1
-    .iter()
-    .map(|stack| String::from(stack[stack.len() - 1]))
-    .collect::<Vec<_>>()
-    .join("");
-  let part2 = board_part2
-    .iter()
-    .map(|stack| String::from(stack[stack.len() - 1]))
-    .collect::<Vec<_>>()
-    .join("");
-  Ok(ReturnType::String(part1, part2))
+  Err("No stack index line".into())
 }
 
-pub fn day05_speed(filename: &Path) -> Result<ReturnType> {
-  let file_content = std::fs::read_to_string(filename)?;
-  let mut content_iterator = file_content.lines();
-  let mut header = Vec::new();
-  let mut bin_size = 0;
-  // Parse header
-  for line in &mut content_iterator {
-    if line.chars().nth(1).unwrap_or('0') == '1' {
-      bin_size = line
-        .chars()
-        .rev()
-        .nth(0)
-        .ok_or("Empty line")?
-        .to_digit(10)
-        .ok_or("Fail to parse digit")? as usize;
-      break;
-    }
-    header.push(line);
-  }
-
-  // Interprete header
-  let mut board_part1 = vec![Vec::new(); bin_size as usize];
-  for header_line in header.iter().rev() {
-    for (index, value) in header_line.chars().skip(1).step_by(4).enumerate() {
-      if value != ' ' {
-        board_part1[index].push(value);
-      }
+/// Move of crates between the stacks at indexes `src` and `dst`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+  pub quantity: usize,
+  pub src: usize,
+  pub dst: usize,
+}
+
+/// Diagram and moves of an input
+pub fn parse(content: &str) -> Result<(Stacks<'_>, Vec<Move>)> {
+  let mut lines = content.lines();
+  let stacks = parse_diagram(&mut lines)?;
+  let regex = Regex::new(r"^move (\d+) from (\d+) to (\d+)$")?;
+  let moves = lines
+    .filter(|line| !line.trim().is_empty())
+    .map(|line| {
+      let cap = regex
+        .captures(line.trim())
+        .ok_or_else(|| format!("Invalid move {:?}", line))?;
+      Ok(Move {
+        quantity: cap[1].parse::<usize>()?,
+        src: stacks.index(cap[2].parse::<u64>()?)?,
+        dst: stacks.index(cap[3].parse::<u64>()?)?,
+      })
+    })
+    .collect::<Result<Vec<_>>>()?;
+  Ok((stacks, moves))
+}
+
+/// What the moves of a crane cost
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cost {
+  pub lifts: u64,
+  pub crates: u64,
+  /// Stacks travelled by the lifts, from the source to the destination
+  pub distance: u64,
+}
+
+impl Cost {
+  fn of_lifts(step: &Move, lifts: usize) -> Self {
+    Self {
+      lifts: lifts as u64,
+      crates: step.quantity as u64,
+      distance: (lifts * step.src.abs_diff(step.dst)) as u64,
     }
   }
+}
+
+impl std::ops::AddAssign for Cost {
+  fn add_assign(&mut self, other: Cost) {
+    self.lifts += other.lifts;
+    self.crates += other.crates;
+    self.distance += other.distance;
+  }
+}
+
+/// How a crane moves the crates of a move
+pub trait CraneModel {
+  fn name(&self) -> String;
+
+  /// Apply a move to the stacks and return what it cost
+  fn apply(&self, stacks: &mut Stacks, step: &Move) -> Result<Cost>;
+}
+
+/// Part1 crane, one crate at a time so the moved crates end up reversed
+#[derive(Debug, Clone, Copy)]
+pub struct CrateMover9000;
+
+impl CraneModel for CrateMover9000 {
+  fn name(&self) -> String {
+    String::from("CrateMover 9000")
+  }
+
+  fn apply(&self, stacks: &mut Stacks, step: &Move) -> Result<Cost> {
+    let lifts = stacks.lift(step.quantity, step.src, step.dst, 1)?;
+    Ok(Cost::of_lifts(step, lifts))
+  }
+}
+
+/// Part2 crane, all the crates of a move at once so they keep their order
+#[derive(Debug, Clone, Copy)]
+pub struct CrateMover9001;
+
+impl CraneModel for CrateMover9001 {
+  fn name(&self) -> String {
+    String::from("CrateMover 9001")
+  }
+
+  fn apply(&self, stacks: &mut Stacks, step: &Move) -> Result<Cost> {
+    let lifts = stacks.lift(step.quantity, step.src, step.dst, step.quantity.max(1))?;
+    Ok(Cost::of_lifts(step, lifts))
+  }
+}
+
+/// Crane lifting at most `capacity` crates at a time, larger moves are split in several lifts
+#[derive(Debug, Clone, Copy)]
+pub struct LimitedCapacity(pub usize);
+
+impl CraneModel for LimitedCapacity {
+  fn name(&self) -> String {
+    format!("capacity {}", self.0)
+  }
+
+  fn apply(&self, stacks: &mut Stacks, step: &Move) -> Result<Cost> {
+    let lifts = stacks.lift(step.quantity, step.src, step.dst, self.0)?;
+    Ok(Cost::of_lifts(step, lifts))
+  }
+}
+
+/// Models of the puzzle, CrateMover 9000 for part1 and 9001 for part2
+pub fn crate_movers() -> Vec<Box<dyn CraneModel>> {
+  vec![Box::new(CrateMover9000), Box::new(CrateMover9001)]
+}
+
+/// Stacks of a crane model after the moves and what they cost
+pub struct Simulation<'a> {
+  pub model: Box<dyn CraneModel>,
+  pub stacks: Stacks<'a>,
+  pub cost: Cost,
+}
+
+/// Moves applied by every model in one pass over the moves
+pub fn simulate<'a>(
+  initial: &Stacks<'a>,
+  moves: &[Move],
+  models: Vec<Box<dyn CraneModel>>,
+) -> Result<Vec<Simulation<'a>>> {
+  let mut simulations = models
+    .into_iter()
+    .map(|model| Simulation {
+      model,
+      stacks: initial.clone(),
+      cost: Cost::default(),
+    })
+    .collect::<Vec<_>>();
+  for step in moves {
+    for simulation in &mut simulations {
+      simulation.cost += simulation.model.apply(&mut simulation.stacks, step)?;
+    }
+  }
+  Ok(simulations)
+}
+
+/// Moves of an input applied one at a time, to show the stacks between the moves
+#[derive(Debug, Clone)]
+pub struct Replay<'a> {
+  pub initial: Stacks<'a>,
+  pub moves: Vec<Move>,
+}
+
+impl<'a> Replay<'a> {
+  pub fn new(content: &'a str) -> Result<Self> {
+    let (initial, moves) = parse(content)?;
+    Ok(Self { initial, moves })
+  }
+
+  /// Move `step` (from 1) as written in the input
+  pub fn describe(&self, step: usize) -> String {
+    match step.checked_sub(1).and_then(|index| self.moves.get(index)) {
+      Some(Move { quantity, src, dst }) => format!(
+        "move {} from {} to {}",
+        quantity, self.initial.ids[*src], self.initial.ids[*dst]
+      ),
+      None => String::from("initial diagram"),
+    }
+  }
+
+  /// Calls `visit` with the initial stacks (step 0) then with the stacks after each move
+  pub fn run(&self, model: &dyn CraneModel, mut visit: impl FnMut(usize, &Stacks<'a>)) -> Result<()> {
+    let mut stacks = self.initial.clone();
+    visit(0, &stacks);
+    for (step, one_move) in self.moves.iter().enumerate() {
+      model
+        .apply(&mut stacks, one_move)
+        .map_err(|e| format!("Move {} ({}): {}", step + 1, self.describe(step + 1), e))?;
+      visit(step + 1, &stacks);
+    }
+    Ok(())
+  }
+
+  /// Stacks after `step` moves, jumping there without keeping the intermediate states
+  pub fn state(&self, step: usize, model: &dyn CraneModel) -> Result<Stacks<'a>> {
+    if step > self.moves.len() {
+      return Err(format!("Step {} after the last move ({})", step, self.moves.len()).into());
+    }
+    let mut stacks = self.initial.clone();
+    for one_move in &self.moves[..step] {
+      model.apply(&mut stacks, one_move)?;
+    }
+    Ok(stacks)
+  }
+}
+
+pub fn day05(input: &Input) -> Result<Solution<String, String>> {
+  let file_content = input.read_to_string()?;
+  let (stacks, moves) = parse(&file_content)?;
+  let simulations = simulate(&stacks, &moves, crate_movers())?;
+  Ok(Solution::new(
+    simulations[0].stacks.tops()?,
+    simulations[1].stacks.tops()?,
+  ))
+}
+
+pub fn day05_speed(input: &Input) -> Result<Solution<String, String>> {
+  let file_content = input.read_to_string()?;
+  let mut content_iterator = file_content.lines();
+  let mut board_part1 = parse_diagram(&mut content_iterator)?;
   let mut board_part2 = board_part1.clone();
 
   // Parse and apply movement
-  // Creating this vector as temporary object inside the loop cost in allocation so much
-  let mut temp_part1 = Vec::new();
-  let mut temp_part2 = Vec::new();
   // the skip is for the empty line between header and movement
   for line in content_iterator.skip(1) {
     // Movement line are of the form "move (\d+) from (\d+) to (\d+)"
-    // on this implementation split take most of the time with a custom iterator using directly memchr I can win 10%
-    let mut splitted = line.split(' ');
-    splitted.next();  // drop "move"
-    let quantity = splitted.next().ok_or("Failed to get quantity")?.parse::<u64>()?;
-    splitted.next();  // drop "from"
-    let src = splitted.next().ok_or("Failed to get src position")?.parse::<u64>()? - 1;
-    splitted.next();  // drop "to"
-    let dst = splitted.next().ok_or("Failed to get src position")?.parse::<u64>()? - 1;
-
-    for _ in 0..quantity {
-      temp_part1.push(board_part1[src as usize].pop().ok_or("No enough value to pop")?);
-      temp_part2.push(board_part2[src as usize].pop().ok_or("No enough value to pop")?);
-    }
-    for elem in &temp_part1 {
-      board_part1[dst as usize].push(*elem);
-    }
-    for elem in temp_part2.iter().rev() {
-      board_part2[dst as usize].push(*elem);
-    }
-    temp_part1.clear();
-    temp_part2.clear();
-  }
-
-  let part1 = board_part1
-    .iter()
-    .map(|stack| String::from(stack[stack.len() - 1]))
-    .collect::<Vec<_>>()
-    .join("");
-  let part2 = board_part2
-    .iter()
-    .map(|stack| String::from(stack[stack.len() - 1]))
-    .collect::<Vec<_>>()
-    .join("");
-  Ok(ReturnType::String(part1, part2))
+    // the tokenizer skip words and directly parse bytes, it was the main cost with split
+    let mut tokens = Tokenizer::new(line.as_bytes());
+    let step = Move {
+      quantity: tokens.next_u64().ok_or("Failed to get quantity")? as usize,
+      src: board_part1.index(tokens.next_u64().ok_or("Failed to get src position")?)?,
+      dst: board_part1.index(tokens.next_u64().ok_or("Failed to get dst position")?)?,
+    };
+    // models called directly, without the dynamic dispatch of `simulate`
+    CrateMover9000.apply(&mut board_part1, &step)?;
+    CrateMover9001.apply(&mut board_part2, &step)?;
+  }
+
+  Ok(Solution::new(board_part1.tops()?, board_part2.tops()?))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
     main:   day05,        "data/day05.txt",              ["ZWHVFWQWW", "HZFZCCWWV"];
-    test1:  day05,        "data/day05_test1.txt",        ["CMZ", "MCD"];
+    test1:  day05,        Example(EXAMPLE),              ["CMZ", "MCD"];
     main:   day05_speed,  "data/day05.txt",              ["ZWHVFWQWW", "HZFZCCWWV"];
-    test1:  day05_speed,  "data/day05_test1.txt",        ["CMZ", "MCD"];
+    test1:  day05_speed,  Example(EXAMPLE),              ["CMZ", "MCD"];
   );
+
+  #[test]
+  fn diagrams() -> Result<()> {
+    let solve = |content: &str| day05(&Input::from_bytes("diagram", content.as_bytes().to_vec()));
+    // 12 stacks, trailing spaces on the index line, multi-character crates and irregular spacing
+    let twelve = "                                    [Z]     [L]
+[A] [B] [C] [D] [E] [F] [G] [H] [I] [J] [K] [XY]
+ 1   2   3   4   5   6   7   8   9   10  11  12  \n
+move 1 from 12 to 10
+move 2 from 10 to 1
+";
+    let tops = |first: &str| format!("{}BCDEFGHIJKXY", first);
+    assert_eq!(solve(twelve)?, Solution::new(tops("Z"), tops("L")));
+    let spaced = "[AB]    [C]
+ [D]  [E]
+  7     3
+
+move 1 from 7 to 3
+";
+    let stacks = Stacks::parse(&["[AB]    [C]", " [D]  [E]"], "  7     3")?;
+    assert_eq!(stacks.ids, vec![7, 3]);
+    assert_eq!(stacks.crates, vec![vec!["D", "AB"], vec!["E", "C"]]);
+    assert_eq!(solve(spaced)?, Solution::new("DAB".to_string(), "DAB".to_string()));
+    assert_eq!(
+      day05_speed(&Input::from_bytes("diagram", twelve.as_bytes().to_vec()))?,
+      solve(twelve)?
+    );
+
+    let error = |content: &str| solve(content).unwrap_err().to_string();
+    assert_eq!(error("[A]\n 1\n\nmove 1 from 2 to 1\n"), "Invalid stack 2");
+    assert_eq!(
+      error("[A]\n 1\n\nmove 2 from 1 to 1\n"),
+      "Can't move 2 crates from stack 1 of 1 crates"
+    );
+    assert_eq!(
+      error("[A]\n 1   2\n\nmove 1 from 1 to 1\n"),
+      "Stack 2 is empty at the end"
+    );
+    assert_eq!(
+      error("    [A]\n[B]\n 1   2\n"),
+      "Crate [A] of stack 2 isn't on top of a crate"
+    );
+    assert_eq!(error("[A]       [B]\n 1   2\n"), "Crate [B] isn't above a stack id");
+    assert_eq!(error("[A]\n 1   1\n"), "Stack 1 appears twice on the index line");
+    assert_eq!(error("A\n 1\n"), r#"Invalid crate "A" in "A""#);
+    Ok(())
+  }
+
+  #[test]
+  fn replay() -> Result<()> {
+    let replay = Replay::new(EXAMPLE)?;
+    let mut diagrams = Vec::new();
+    replay.run(&CrateMover9001, |step, stacks| diagrams.push((step, stacks.render())))?;
+    assert_eq!(diagrams.len(), 5);
+    assert_eq!(diagrams[0].1, EXAMPLE[..EXAMPLE.find("\n\n").unwrap() + 1]);
+    assert_eq!(
+      diagrams[2].1,
+      "        [D]\n        [N]\n    [C] [Z]\n    [M] [P]\n 1   2   3\n"
+    );
+    assert_eq!(replay.state(2, &CrateMover9001)?.render(), diagrams[2].1);
+    assert_eq!(replay.describe(2), "move 3 from 1 to 3");
+    assert_eq!(replay.state(4, &CrateMover9000)?.tops()?, "CMZ");
+    assert!(replay.state(5, &CrateMover9000).is_err());
+    assert_eq!(
+      replay
+        .state(1, &CrateMover9000)?
+        .diff(&replay.state(2, &CrateMover9000)?),
+      "stack 1: [Z] [N] [D] -> \nstack 3: [P] -> [P] [D] [N] [Z]\n"
+    );
+    // drawn diagrams read back the same, wide columns included
+    let (stacks, _) = parse("[LONG]       \n[A]    [B]\n  10     2\n")?;
+    assert_eq!(stacks.crates, vec![vec!["A", "LONG"], vec!["B"]]);
+    let drawn = stacks.render();
+    let lines = drawn.lines().collect::<Vec<_>>();
+    assert_eq!(Stacks::parse(&lines[..2], lines[2])?, stacks);
+    Ok(())
+  }
+
+  #[test]
+  fn crane_models() -> Result<()> {
+    let (stacks, moves) = parse(EXAMPLE)?;
+    let mut models = crate_movers();
+    models.extend([1, 2, 3].map(|capacity| Box::new(LimitedCapacity(capacity)) as Box<dyn CraneModel>));
+    let simulations = simulate(&stacks, &moves, models)?;
+    let results = simulations
+      .iter()
+      .map(|simulation| Ok((simulation.model.name(), simulation.stacks.tops()?, simulation.cost)))
+      .collect::<Result<Vec<_>>>()?;
+    let cost = |lifts, distance| Cost {
+      lifts,
+      crates: 7,
+      distance,
+    };
+    // moves of 1, 3, 2 and 1 crates over 1, 2, 1 and 1 stacks
+    assert_eq!(
+      results[0],
+      (String::from("CrateMover 9000"), String::from("CMZ"), cost(7, 10))
+    );
+    assert_eq!(
+      results[1],
+      (String::from("CrateMover 9001"), String::from("MCD"), cost(4, 5))
+    );
+    assert_eq!(
+      results[2],
+      (String::from("capacity 1"), String::from("CMZ"), cost(7, 10))
+    );
+    // 3 crates in lifts of 2 then 1: [N D] then [Z] on top of [P]
+    assert_eq!(
+      results[3],
+      (String::from("capacity 2"), String::from("MCZ"), cost(5, 7))
+    );
+    assert_eq!(
+      results[4],
+      (String::from("capacity 3"), String::from("MCD"), cost(4, 5))
+    );
+
+    let mut stacks = stacks;
+    let on_itself = Move {
+      quantity: 2,
+      src: 0,
+      dst: 0,
+    };
+    assert_eq!(CrateMover9000.apply(&mut stacks, &on_itself)?.lifts, 2);
+    assert_eq!(stacks.crates[0], vec!["Z", "N"]);
+    assert!(LimitedCapacity(0).apply(&mut stacks, &on_itself).is_err());
+    Ok(())
+  }
 }
@@ -1,12 +1,14 @@
 // #![allow(unused_variables)]
 use itertools::Itertools;
-use std::path::Path;
+use std::io::BufRead;
 
-use crate::utils::ReturnType;
+use crate::input::Input;
+use crate::utils::Solution;
+use crate::tokenizer::lines;
 use crate::Result;
 
-pub fn day06(filename: &Path) -> Result<ReturnType> {
-  let file_content = std::fs::read_to_string(filename)?;
+pub fn day06(input: &Input) -> Result<Solution<u64, u64>> {
+  let file_content = input.read_to_string()?;
   let content = file_content.lines().next().ok_or("Empty File")?;
 
   let part1 = content
@@ -46,18 +48,16 @@ pub fn day06(filename: &Path) -> Result<ReturnType> {
     .next()
     .ok_or("No message found")?;
 
-  Ok(ReturnType::Numeric(part1 as u64, part2 as u64))
+  Ok(Solution::new(part1 as u64, part2 as u64))
 }
 
 
-pub fn day06_speed(filename: &Path) -> Result<ReturnType> {
-  let file_content = std::fs::read_to_string(filename)?;
-  let content = file_content.lines().next().ok_or("Empty File")?;
+pub fn day06_speed(input: &Input) -> Result<Solution<u64, u64>> {
+  let file_content = input.read_bytes()?;
+  let content = lines(&file_content).next().ok_or("Empty File")?;
 
   const MARKER_LEN: usize = 4;
   let part1 = content
-    .chars()
-    .collect::<Vec<_>>()
     .windows(MARKER_LEN)
     .enumerate()
     .find_map(|(index, chars)| {
@@ -72,8 +72,6 @@ pub fn day06_speed(filename: &Path) -> Result<ReturnType> {
 
   const MESSAGE_LEN: usize = 14;
   let part2 = content
-    .chars()
-    .collect::<Vec<_>>()
     .windows(MESSAGE_LEN)
     .enumerate()
     .find_map(|(index, chars)| {
@@ -87,19 +85,67 @@ pub fn day06_speed(filename: &Path) -> Result<ReturnType> {
     })
     .ok_or("No message found")?;
 
-  Ok(ReturnType::Numeric(part1 as u64, part2 as u64))
+  Ok(Solution::new(part1 as u64, part2 as u64))
+}
+
+// Streaming version, we keep the position of the last occurence of each character
+// to know the length of the current sequence of distinct characters.
+// Both parts are found in the same pass and memory doesn't depend on input size
+pub fn day06_stream(input: &Input) -> Result<Solution<u64, u64>> {
+  const MARKER_LEN: usize = 4;
+  const MESSAGE_LEN: usize = 14;
+  let mut reader = input.reader()?;
+  // position (starting at 1) of the last occurence of each character, 0 if never seen
+  let mut last_seen = [0usize; 256];
+  // the current sequence of distinct characters start after this position
+  let mut sequence_start = 0;
+  let mut position = 0;
+  let mut part1 = None;
+  let part2 = 'stream: loop {
+    let buffer = reader.fill_buf()?;
+    if buffer.is_empty() {
+      break None;
+    }
+    let buffer_len = buffer.len();
+    for &byte in buffer {
+      // only the first line is used
+      if byte == b'\n' || byte == b'\r' {
+        break 'stream None;
+      }
+      position += 1;
+      sequence_start = sequence_start.max(last_seen[byte as usize]);
+      last_seen[byte as usize] = position;
+      let sequence_len = position - sequence_start;
+      if part1.is_none() && sequence_len >= MARKER_LEN {
+        part1 = Some(position);
+      }
+      if sequence_len >= MESSAGE_LEN {
+        break 'stream Some(position);
+      }
+    }
+    reader.consume(buffer_len);
+  };
+  let part1 = part1.ok_or("No marker found")?;
+  let part2 = part2.ok_or("No message found")?;
+
+  Ok(Solution::new(part1 as u64, part2 as u64))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"mjqjpqmgbljsphdztnvjfqwrcgsmlb
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
     main:   day06,        "data/day06.txt",              [1282, 3513];
-    test1:  day06,        "data/day06_test1.txt",        [7, 19];
+    test1:  day06,        Example(EXAMPLE),              [7, 19];
     main:   day06_speed,  "data/day06.txt",              [1282, 3513];
-    test1:  day06_speed,  "data/day06_test1.txt",        [7, 19];
+    test1:  day06_speed,  Example(EXAMPLE),              [7, 19];
+    main:   day06_stream, "data/day06.txt",              [1282, 3513];
+    test1:  day06_stream, Example(EXAMPLE),              [7, 19];
   );
 }
@@ -1,8 +1,7 @@
 // #![allow(unused_variables)]
-use std::collections::HashMap;
-use std::path::Path;
-
-use crate::utils::ReturnType;
+use crate::hash::FastHashMap;
+use crate::input::Input;
+use crate::utils::Solution;
 use crate::Result;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -18,8 +17,8 @@ struct Entry {
   parent: Option<usize>,
 }
 
-pub fn day07(filename: &Path) -> Result<ReturnType> {
-  let mut tree_index: HashMap<String, usize> = HashMap::new();
+pub fn day07(input: &Input) -> Result<Solution<u64, u64>> {
+  let mut tree_index: FastHashMap<String, usize> = FastHashMap::default();
   let mut tree_content: Vec<Entry> = Vec::new();
 
   // I can't make the HashMap<String, Option<Entry>> work on the second
@@ -33,7 +32,7 @@ pub fn day07(filename: &Path) -> Result<ReturnType> {
     parent: None,
   });
 
-  for full_command_with_result in std::fs::read_to_string(filename)?.split("$").skip(1) {
+  for full_command_with_result in input.read_to_string()?.split("$").skip(1) {
     let mut splitted_by_line = full_command_with_result.split("\n");
     // Check command
     let mut full_command = splitted_by_line.next().ok_or("Empty Line Found")?.trim().split(" ");
@@ -99,7 +98,9 @@ pub fn day07(filename: &Path) -> Result<ReturnType> {
       let Some(parent_index) = tree_content[current_idx].parent else {
           break;
         };
-      tree_content[parent_index].size += tree_content[original_index].size;
+      let size = tree_content[original_index].size;
+      let parent = &mut tree_content[parent_index];
+      parent.size = parent.size.checked_add(size).ok_or("Directory size overflow")?;
       current_idx = parent_index;
     }
   }
@@ -116,7 +117,7 @@ pub fn day07(filename: &Path) -> Result<ReturnType> {
     .sum::<usize>();
 
   let total_used_space = tree_content[0].size;
-  let space_to_free = 30_000_000 - (70_000_000 - total_used_space);
+  let space_to_free = (30_000_000 + total_used_space).saturating_sub(70_000_000);
   let mut part2_list = tree_content
     .iter()
     .filter_map(|entry| {
@@ -128,18 +129,43 @@ pub fn day07(filename: &Path) -> Result<ReturnType> {
     })
     .collect::<Vec<_>>();
   part2_list.sort();
-  let part2 = part2_list[0];
-  Ok(ReturnType::Numeric(part1 as u64, part2 as u64))
+  let part2 = *part2_list.first().ok_or("No directory to delete")?;
+  Ok(Solution::new(part1 as u64, part2 as u64))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
     main:   day07,        "data/day07.txt",              [1543140, 1117448];
-    test1:  day07,        "data/day07_test1.txt",        [95437, 24933642];
+    test1:  day07,        Example(EXAMPLE),              [95437, 24933642];
   );
 }
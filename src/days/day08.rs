@@ -1,7 +1,8 @@
 // #![allow(unused_variables)]
-use std::path::Path;
 
-use crate::utils::ReturnType;
+use crate::input::Input;
+use crate::utils::Solution;
+use crate::tokenizer::lines;
 use crate::Result;
 
 #[derive(Debug, Clone)]
@@ -44,15 +45,18 @@ impl std::fmt::Display for Board {
 }
 
 // I use vectorized board, that reduce the boundary check on vector access compare to vector of vector
-pub fn day08(filename: &Path) -> Result<ReturnType> {
+pub fn day08(input: &Input) -> Result<Solution<u64, u64>> {
   let mut board = Board {
     data: Vec::new(),
     width: 0,
   };
-  for line in std::fs::read_to_string(filename)?.lines() {
+  for line in input.read_to_string()?.lines() {
     if board.width == 0 {
       board.width = line.chars().count();
     }
+    if line.chars().count() != board.width {
+      return Err("Lines of different width".into());
+    }
     for tree in line.chars() {
       board
         .data
@@ -60,6 +64,9 @@ pub fn day08(filename: &Path) -> Result<ReturnType> {
     }
   }
 
+  if board.data.is_empty() {
+    return Err("Empty forest".into());
+  }
   let mut part1 = (board.get_height() * 2 + board.width * 2 - 4) as u64;
   let mut part2 = 0;
   for y in 1..board.get_height() - 1 {
@@ -117,27 +124,35 @@ pub fn day08(filename: &Path) -> Result<ReturnType> {
       part2 = part2.max(top * bottom * left * right);
     }
   }
-  Ok(ReturnType::Numeric(part1, part2))
+  Ok(Solution::new(part1, part2))
 }
 
 
 // I use vectorized board, that reduce the boundary check on vector access compare to vector of vector
-pub fn day08_speed(filename: &Path) -> Result<ReturnType> {
+pub fn day08_speed(input: &Input) -> Result<Solution<u64, u64>> {
   let mut board = Board {
     data: Vec::new(),
     width: 0,
   };
-  for line in std::fs::read_to_string(filename)?.lines() {
+  for line in lines(&input.read_bytes()?) {
     if board.width == 0 {
-      board.width = line.chars().count();
+      board.width = line.len();
     }
-    for tree in line.chars() {
-      board
-        .data
-        .push(tree.to_digit(10).ok_or("Fail to parse tree height")? as u8);
+    if line.len() != board.width {
+      return Err("Lines of different width".into());
+    }
+    for tree in line {
+      let height = tree.wrapping_sub(b'0');
+      if height > 9 {
+        return Err("Fail to parse tree height".into());
+      }
+      board.data.push(height);
     }
   }
 
+  if board.data.is_empty() {
+    return Err("Empty forest".into());
+  }
   let mut part1 = (board.get_height() * 2 + board.width * 2 - 4) as u64;
   let mut part2 = 0;
   for y in 1..board.get_height() - 1 {
@@ -195,19 +210,26 @@ pub fn day08_speed(filename: &Path) -> Result<ReturnType> {
       part2 = part2.max(top * bottom * left * right);
     }
   }
-  Ok(ReturnType::Numeric(part1, part2))
+  Ok(Solution::new(part1, part2))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"30373
+25512
+65332
+33549
+35390
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
     main:   day08,        "data/day08.txt",              [1688, 410400];
-    test1:  day08,        "data/day08_test1.txt",        [21, 8];
+    test1:  day08,        Example(EXAMPLE),              [21, 8];
     main:   day08_speed,  "data/day08.txt",              [1688, 410400];
-    test1:  day08_speed,  "data/day08_test1.txt",        [21, 8];
+    test1:  day08_speed,  Example(EXAMPLE),              [21, 8];
   );
 }
@@ -1,12 +1,13 @@
 // #![allow(unused_variables)]
-use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
-use std::path::Path;
 
-use crate::utils::ReturnType;
+use crate::hash::FastHashSet;
+use crate::input::{Input, LineReader};
+use crate::utils::{get_many_mut, Solution};
+use crate::tokenizer::{lines, parse_u64};
 use crate::Result;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 struct Point {
   x: i32,
   y: i32
@@ -18,23 +19,6 @@ impl Point {
   }
 }
 
-/// like split_at_mut but we give 2 elems instead of 2 slice
-fn take2_at_mut(values: &mut [Point], index1: usize, index2: usize) -> (&mut Point, &mut Point) {
-    let len = values.len();
-    let ptr = values.as_mut_ptr();
-
-    debug_assert!(index1 <= len);
-    debug_assert!(index2 <= len);
-    debug_assert!(index1 != index2);
-
-    unsafe {
-        (
-          &mut *ptr.add(index1),
-          &mut *ptr.add(index2),
-        )
-    }
-}
-
 impl Hash for Point {
     fn hash<H: Hasher>(&self, state: &mut H) {
       let x_byte: [u8; 4] = self.x.to_ne_bytes();
@@ -51,16 +35,27 @@ impl Hash for Point {
     }
 }
 
-pub fn day09(filename: &Path) -> Result<ReturnType> {
+/// Total number of head steps, the visited positions are kept in sets that grow with them
+const MAX_STEPS: u64 = 1 << 22;
 
-  let mut tail_visited_position_part1: HashSet<(i32, i32)> = HashSet::new();
-  let mut tail_visited_position_part2: HashSet<(i32, i32)> = HashSet::new();
+pub fn day09(input: &Input) -> Result<Solution<u64, u64>> {
+
+  let mut tail_visited_position_part1: FastHashSet<(i32, i32)> = FastHashSet::default();
+  let mut tail_visited_position_part2: FastHashSet<(i32, i32)> = FastHashSet::default();
   let mut rope = vec![Point{x:0, y:0}; 10];
+  let mut steps = 0;
 
-  for line in std::fs::read_to_string(filename)?.lines() {
+  for line in input.read_to_string()?.lines() {
     let mut line_splitted = line.split(" ");
-    let direction = line_splitted.next().unwrap();
-    let quantity = line_splitted.next().unwrap().parse::<u32>()?;
+    let direction = line_splitted.next().ok_or("Missing direction")?;
+    let quantity = line_splitted.next().ok_or("Missing quantity")?.parse::<u32>()?;
+    if !matches!(direction, "U" | "D" | "L" | "R") {
+      return Err(format!("Unsupported character {}", direction).into());
+    }
+    steps += quantity as u64;
+    if steps > MAX_STEPS {
+      return Err("Rope path too long".into());
+    }
     for _ in 0..quantity {
       let mut head = &mut rope[0];
       match direction {
@@ -76,17 +71,17 @@ pub fn day09(filename: &Path) -> Result<ReturnType> {
         "R" => {
           head.x += 1;
         },
-        _ => panic!("Unsupported character {}", direction)
+        _ => unreachable!("direction checked when parsing"),
       }
 
       for index in 1..rope.len() {
-        let (head, tail) = take2_at_mut(&mut rope[..], index-1, index);
-        // take2_at_mut can be replace by split_at_mut, there is almost no speed diff
+        let [head, tail] = get_many_mut(&mut rope, [index - 1, index])?;
+        // get_many_mut can be replace by split_at_mut, there is almost no speed diff
         // let (rope1, rope2) = rope.split_at_mut(index);
         // let head = &rope1[index-1];
         // let mut tail = &mut rope2[0];
 
-        if tail.distance(&head) > 1 {
+        if tail.distance(head) > 1 {
           tail.x += (head.x - tail.x).signum();
           tail.y += (head.y - tail.y).signum();
         }
@@ -97,77 +92,157 @@ pub fn day09(filename: &Path) -> Result<ReturnType> {
   }
   let part1 = tail_visited_position_part1.len();
   let part2 = tail_visited_position_part2.len();
-  Ok(ReturnType::Numeric(part1 as u64, part2 as u64))
+  Ok(Solution::new(part1 as u64, part2 as u64))
 }
 
 
-pub fn day09_speed(filename: &Path) -> Result<ReturnType> {
-
-  // use big vec instead of hashset it divise by 2 the time but is more uncertain
-  // with my puzzle input 500 is enough
-  const MAX_DIM : usize = 500;
-  let mut tail_visited_position_part1 = [false; MAX_DIM*MAX_DIM];
-  let mut tail_visited_position_part2 = [false; MAX_DIM*MAX_DIM];
+pub fn day09_speed(input: &Input) -> Result<Solution<u64, u64>> {
+
+  // use big vec instead of hashset it divise by 2 the time
+  // the grid is sized with the head bounding box as tails never go outside of it
+  let mut moves = Vec::new();
+  let mut head = Point { x: 0, y: 0 };
+  let (mut min, mut max) = (head, head);
+  for line in lines(&input.read_bytes()?) {
+    let direction = *line.first().ok_or("Missing direction")?;
+    let quantity = i32::try_from(parse_u64(line.get(2..).ok_or("Missing quantity")?)?)?;
+    let moved = match direction {
+      b'U' => head.y.checked_sub(quantity).map(|y| Point { y, ..head }),
+      b'D' => head.y.checked_add(quantity).map(|y| Point { y, ..head }),
+      b'L' => head.x.checked_sub(quantity).map(|x| Point { x, ..head }),
+      b'R' => head.x.checked_add(quantity).map(|x| Point { x, ..head }),
+      _ => return Err(format!("Unsupported character {}", direction as char).into()),
+    };
+    head = moved.ok_or("Rope too far away")?;
+    min = Point { x: min.x.min(head.x), y: min.y.min(head.y) };
+    max = Point { x: max.x.max(head.x), y: max.y.max(head.y) };
+    moves.push((direction, quantity));
+  }
+  let width = (max.x as i64 - min.x as i64 + 1) as usize;
+  let height = (max.y as i64 - min.y as i64 + 1) as usize;
+  let area = width.checked_mul(height).filter(|area| *area <= 1 << 30).ok_or("Rope area too large")?;
+  let index = |point: &Point| (point.x - min.x) as usize * height + (point.y - min.y) as usize;
+  let mut tail_visited_position_part1 = vec![false; area];
+  let mut tail_visited_position_part2 = vec![false; area];
   let mut rope = vec![Point{x:0, y:0}; 10];
   let mut part1 = 0;
   let mut part2 = 0;
-  for line in std::fs::read_to_string(filename)?.lines() {
-    let mut line_splitted = line.split(" ");
-    let direction = line_splitted.next().unwrap();
-    let quantity = line_splitted.next().unwrap().parse::<u32>()?;
+  for (direction, quantity) in moves {
     for _ in 0..quantity {
-      let mut head = &mut rope[0];
+      let head = &mut rope[0];
       match direction {
-        "U" => {
+        b'U' => {
           head.y -= 1;
         },
-        "D" => {
+        b'D' => {
           head.y += 1;
         },
-        "L" => {
+        b'L' => {
           head.x -= 1;
         },
-        "R" => {
+        b'R' => {
           head.x += 1;
         },
-        _ => panic!("Unsupported character {}", direction)
+        _ => unreachable!("direction checked when parsing"),
       }
 
       for index in 1..rope.len() {
-        let (head, tail) = take2_at_mut(&mut rope[..], index-1, index);
-        // take2_at_mut can be replace by split_at_mut, there is almost no speed diff
+        let [head, tail] = get_many_mut(&mut rope, [index - 1, index])?;
+        // get_many_mut can be replace by split_at_mut, there is almost no speed diff
         // let (rope1, rope2) = rope.split_at_mut(index);
         // let head = &rope1[index-1];
         // let mut tail = &mut rope2[0];
 
-        if tail.distance(&head) > 1 {
+        if tail.distance(head) > 1 {
           tail.x += (head.x - tail.x).signum();
           tail.y += (head.y - tail.y).signum();
         }
       }
-      let temp1 = &mut tail_visited_position_part1[((MAX_DIM as i32 / 2 + rope[1].x) * MAX_DIM as i32 + (MAX_DIM as i32 / 2 + rope[1].y)) as usize];
+      let temp1 = &mut tail_visited_position_part1[index(&rope[1])];
       if !*temp1{
         part1 += 1;
         *temp1 = true;
       };
-      let temp2 = &mut tail_visited_position_part2[((MAX_DIM as i32 / 2 + rope[9].x) * MAX_DIM as i32 + (MAX_DIM as i32 / 2 + rope[9].y)) as usize];
+      let temp2 = &mut tail_visited_position_part2[index(&rope[9])];
       if !*temp2 {
         part2 += 1;
         *temp2 = true;
       };
     }
   }
-  Ok(ReturnType::Numeric(part1 as u64, part2 as u64))
+  Ok(Solution::new(part1 as u64, part2 as u64))
+}
+
+// Streaming version, memory only depend on the number of visited positions
+pub fn day09_stream(input: &Input) -> Result<Solution<u64, u64>> {
+  let mut lines = LineReader::new(input.reader()?);
+  let mut tail_visited_position_part1: FastHashSet<(i32, i32)> = FastHashSet::default();
+  let mut tail_visited_position_part2: FastHashSet<(i32, i32)> = FastHashSet::default();
+  let mut rope = vec![Point { x: 0, y: 0 }; 10];
+  let mut steps = 0;
+
+  while let Some(line) = lines.next_line()? {
+    let mut line_splitted = line.split(' ');
+    let direction = line_splitted.next().ok_or("Missing direction")?;
+    let quantity = line_splitted.next().ok_or("Missing quantity")?.parse::<u32>()?;
+    let (dx, dy) = match direction {
+      "U" => (0, -1),
+      "D" => (0, 1),
+      "L" => (-1, 0),
+      "R" => (1, 0),
+      _ => return Err(format!("Unsupported character {}", direction).into()),
+    };
+    steps += quantity as u64;
+    if steps > MAX_STEPS {
+      return Err("Rope path too long".into());
+    }
+    for _ in 0..quantity {
+      rope[0].x += dx;
+      rope[0].y += dy;
+
+      for index in 1..rope.len() {
+        let [head, tail] = get_many_mut(&mut rope, [index - 1, index])?;
+        if tail.distance(head) > 1 {
+          tail.x += (head.x - tail.x).signum();
+          tail.y += (head.y - tail.y).signum();
+        }
+      }
+      tail_visited_position_part1.insert((rope[1].x, rope[1].y));
+      tail_visited_position_part2.insert((rope[9].x, rope[9].y));
+    }
+  }
+  let part1 = tail_visited_position_part1.len();
+  let part2 = tail_visited_position_part2.len();
+  Ok(Solution::new(part1 as u64, part2 as u64))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"R 5
+U 8
+L 8
+D 3
+R 17
+D 10
+L 25
+U 20
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
     main:   day09,        "data/day09.txt",              [6503, 2724];
-    test1:  day09,        "data/day09_test1.txt",        [88, 36];
+    test1:  day09,        Example(EXAMPLE),              [88, 36];
+    main:   day09_stream, "data/day09.txt",              [6503, 2724];
+    test1:  day09_stream, Example(EXAMPLE),              [88, 36];
   );
+
+  #[test]
+  fn path_too_long() {
+    let input = Input::from_bytes("path", b"R 4294967295\n".to_vec());
+    assert!(day09(&input).is_err());
+    assert!(day09_stream(&input).is_err());
+  }
 }
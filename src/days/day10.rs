@@ -1,31 +1,33 @@
 // #![allow(unused_variables)]
-use std::path::Path;
 
 use itertools::Itertools;
 
-use crate::utils::ReturnType;
+use crate::input::{Input, LineReader};
+use crate::utils::Solution;
 use crate::Result;
 
-pub fn day10(filename: &Path) -> Result<ReturnType> {
+const CRT_WIDTH: usize = 40;
+const CRT_HEIGHT: usize = 6;
+
+pub fn day10(input: &Input) -> Result<Solution<u64, String>> {
   let mut register_value: Vec<i32> = Vec::new();
-  const CRT_WIDTH: usize = 40;
-  const CRT_HEIGHT: usize = 6;
   register_value.reserve(CRT_WIDTH * CRT_HEIGHT);
   let mut current_value = 1;
   register_value.push(current_value);  // to compensate zero indexing of array
   register_value.push(current_value);  // offset to compensate the fact the register is set at the end of cycle
 
-  for line in std::fs::read_to_string(filename)?.lines() {
+  for line in input.read_to_string()?.lines() {
     let mut full_command = line.split(" ");
     let command = full_command.next().ok_or("Empty Line Found")?;
     match command {
       "noop" => register_value.push(current_value),
       "addx" => {
         register_value.push(current_value);
-        current_value += full_command
+        let increment = full_command
         .next()
         .ok_or("No Increment found in addx command")?
         .parse::<i32>()?;
+        current_value = current_value.checked_add(increment).ok_or("Register overflow")?;
         register_value.push(current_value);
       }
       _ => Err("Unknown command")?,
@@ -35,10 +37,14 @@ pub fn day10(filename: &Path) -> Result<ReturnType> {
   let mut screen: Vec<char> = vec!['.'; CRT_WIDTH * CRT_HEIGHT];
   let mut part1 = 0;
   for cycle in 1..screen.len() {
+    // the last value is the register after the program, its cycle never runs
+    if cycle + 1 >= register_value.len() {
+      break;
+    }
     if (20..=220).step_by(40).contains(&cycle) {
-      part1 += register_value[cycle] * cycle as i32;
+      part1 = signal_strength(part1, register_value[cycle], cycle)?;
     }
-    if ((cycle-1).rem_euclid(40) as i32 - register_value[cycle]).abs() <= 1 {
+    if ((cycle-1).rem_euclid(40) as i64 - register_value[cycle] as i64).abs() <= 1 {
       screen[cycle-1] = '#';
     }
   }
@@ -55,17 +61,262 @@ pub fn day10(filename: &Path) -> Result<ReturnType> {
   // #....###..#....###..#....###..#.......#.
   // #....#.#..#..#.#.#..#....#....#..#.#..#.
   // ####.#..#..##..#..#.####.#.....##...##..
-  Ok(ReturnType::Numeric(part1 as u64, 2 as u64))
+  Ok(Solution::new(part1 as u64, read_letters(&screen)))
+}
+
+/// Add the signal strength of `cycle` to `total`
+fn signal_strength(total: i32, register: i32, cycle: usize) -> Result<i32> {
+  register
+    .checked_mul(cycle as i32)
+    .and_then(|strength| total.checked_add(strength))
+    .ok_or_else(|| "Signal strength overflow".into())
+}
+
+/// Letters of the puzzle font, each glyph is 4 pixels wide and 6 pixels high, rows are concatenated
+const GLYPHS: [(char, &str); 17] = [
+  ('A', ".##.#..##..######..##..#"),
+  ('B', "###.#..####.#..##..####."),
+  ('C', ".##.#..##...#...#..#.##."),
+  ('E', "#####...###.#...#...####"),
+  ('F', "#####...###.#...#...#..."),
+  ('G', ".##.#..##...#.###..#.###"),
+  ('H', "#..##..######..##..##..#"),
+  ('I', ".###..#...#...#...#..###"),
+  ('J', "..##...#...#...##..#.##."),
+  ('K', "#..##.#.##..#.#.#.#.#..#"),
+  ('L', "#...#...#...#...#...####"),
+  ('O', ".##.#..##..##..##..#.##."),
+  ('P', "###.#..##..####.#...#..."),
+  ('R', "###.#..##..####.#.#.#..#"),
+  ('S', ".####...#....##....####."),
+  ('U', "#..##..##..##..##..#.##."),
+  ('Z', "####...#..#..#..#...####"),
+];
+
+/// Read the letters drawn on the screen, `?` for a glyph not in the font
+fn read_letters(screen: &[char]) -> String {
+  (0..CRT_WIDTH / 5)
+    .map(|letter| {
+      let pixels = (0..CRT_HEIGHT)
+        .flat_map(|row| &screen[row * CRT_WIDTH + letter * 5..row * CRT_WIDTH + letter * 5 + 4])
+        .collect::<String>();
+      GLYPHS
+        .iter()
+        .find(|(_, glyph)| *glyph == pixels)
+        .map_or('?', |(letter, _)| *letter)
+    })
+    .collect()
+}
+/// Screen and signal strength updated at each cycle
+struct Crt {
+  cycle: usize,
+  signal_strength: i32,
+  screen: [char; CRT_WIDTH * CRT_HEIGHT],
+}
+
+impl Crt {
+  /// Run one cycle, `register` is the register value during the cycle
+  fn tick(&mut self, register: i32) -> Result<()> {
+    self.cycle += 1;
+    if (20..=220).step_by(40).contains(&self.cycle) {
+      self.signal_strength = signal_strength(self.signal_strength, register, self.cycle)?;
+    }
+    let pixel = self.cycle - 1;
+    if pixel < self.screen.len() && ((pixel % CRT_WIDTH) as i64 - register as i64).abs() <= 1 {
+      self.screen[pixel] = '#';
+    }
+    Ok(())
+  }
+}
+
+// Streaming version, the register value is not stored so memory doesn't depend on input size
+pub fn day10_stream(input: &Input) -> Result<Solution<u64, String>> {
+  let mut lines = LineReader::new(input.reader()?);
+  let mut crt = Crt {
+    cycle: 0,
+    signal_strength: 0,
+    screen: ['.'; CRT_WIDTH * CRT_HEIGHT],
+  };
+  let mut register = 1;
+  while let Some(line) = lines.next_line()? {
+    let mut full_command = line.split(' ');
+    let command = full_command.next().ok_or("Empty Line Found")?;
+    match command {
+      "noop" => crt.tick(register)?,
+      "addx" => {
+        crt.tick(register)?;
+        crt.tick(register)?;
+        let increment = full_command
+          .next()
+          .ok_or("No Increment found in addx command")?
+          .parse::<i32>()?;
+        register = register.checked_add(increment).ok_or("Register overflow")?;
+      }
+      _ => Err("Unknown command")?,
+    }
+  }
+
+  Ok(Solution::new(crt.signal_strength as u64, read_letters(&crt.screen)))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"addx 15
+addx -11
+addx 6
+addx -3
+addx 5
+addx -1
+addx -8
+addx 13
+addx 4
+noop
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx -35
+addx 1
+addx 24
+addx -19
+addx 1
+addx 16
+addx -11
+noop
+noop
+addx 21
+addx -15
+noop
+noop
+addx -3
+addx 9
+addx 1
+addx -3
+addx 8
+addx 1
+addx 5
+noop
+noop
+noop
+noop
+noop
+addx -36
+noop
+addx 1
+addx 7
+noop
+noop
+noop
+addx 2
+addx 6
+noop
+noop
+noop
+noop
+noop
+addx 1
+noop
+noop
+addx 7
+addx 1
+noop
+addx -13
+addx 13
+addx 7
+noop
+addx 1
+addx -33
+noop
+noop
+noop
+addx 2
+noop
+noop
+noop
+addx 8
+noop
+addx -1
+addx 2
+addx 1
+noop
+addx 17
+addx -9
+addx 1
+addx 1
+addx -3
+addx 11
+noop
+noop
+addx 1
+noop
+addx 1
+noop
+noop
+addx -13
+addx -19
+addx 1
+addx 3
+addx 26
+addx -30
+addx 12
+addx -1
+addx 3
+addx 1
+noop
+noop
+noop
+addx -9
+addx 18
+addx 1
+addx 2
+noop
+noop
+addx 9
+noop
+noop
+noop
+addx -1
+addx 2
+addx -37
+addx 1
+addx 3
+noop
+addx 15
+addx -21
+addx 22
+addx -6
+addx 1
+noop
+addx 2
+addx 1
+noop
+addx -10
+noop
+noop
+addx 20
+addx 1
+addx 2
+addx 2
+addx -6
+addx -11
+noop
+noop
+noop
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
-    main:   day10,        "data/day10.txt",              [11720, 2];
-    test1:  day10,        "data/day10_test1.txt",        [13140, 2];
+    main:   day10,        "data/day10.txt",              [11720, "ERCREPCJ"];
+    test1:  day10,        Example(EXAMPLE),              [13140, "????????"];
+    main:   day10_stream, "data/day10.txt",              [11720, "ERCREPCJ"];
+    test1:  day10_stream, Example(EXAMPLE),              [13140, "????????"];
   );
 }
@@ -1,8 +1,10 @@
 // #![allow(unused_variables)]
 use regex::Regex;
-use std::path::Path;
 
-use crate::utils::ReturnType;
+use crate::input::Input;
+use crate::utils::{get_many_mut, Solution};
+use crate::tokenizer::{lines, parse_u64, Tokenizer};
+use crate::trace;
 use crate::Result;
 // Monkey 0:
 //   Starting items: 79, 98
@@ -15,36 +17,39 @@ use crate::Result;
 //   - can't use : fn(usize) -> usize because I need to capture
 //   - can't use : &'a dyn Fn(usize) -> usize because the lambda don't live long enough
 struct Monkey {
-  operation: Box<dyn Fn(u64) -> u64>,
+  operation: Box<dyn Fn(u64) -> Option<u64>>,
   div_test: u64,
   monkey_if_true: usize,
   monkey_if_false: usize,
 }
 
-/// like split_at_mut but we give 3 elems instead of 2 slice
-fn take3_at_mut(
-  values: &mut [Vec<u64>],
-  index1: usize,
-  index2: usize,
-  index3: usize,
-) -> (&mut Vec<u64>, &mut Vec<u64>, &mut Vec<u64>) {
-  let len = values.len();
-  let ptr = values.as_mut_ptr();
-
-  assert!(index1 <= len);
-  assert!(index2 <= len);
-  assert!(index3 <= len);
-  assert!(index1 != index2);
-  assert!(index1 != index3);
-  assert!(index2 != index3);
-
-  unsafe { (&mut *ptr.add(index1), &mut *ptr.add(index2), &mut *ptr.add(index3)) }
+/// Multiply `ppcm` by a new divisor, the divisor is used for modulo so it can't be 0
+fn check_divisor(ppcm: u64, div_test: u64) -> Result<u64> {
+  if div_test == 0 {
+    return Err("Divisible by 0".into());
+  }
+  Ok(ppcm.checked_mul(div_test).ok_or("Divisors product overflow")?)
 }
 
-pub fn day11(filename: &Path) -> Result<ReturnType> {
+/// At least two monkeys, all throwing to a monkey that exists
+fn check_targets(targets: impl ExactSizeIterator<Item = (usize, usize)>) -> Result<()> {
+  let count = targets.len();
+  if count < 2 {
+    return Err("Less than 2 monkeys".into());
+  }
+  for (if_true, if_false) in targets {
+    if if_true >= count || if_false >= count {
+      return Err("Throw to an unknown monkey".into());
+    }
+  }
+  Ok(())
+}
+
+pub fn day11(input: &Input) -> Result<Solution<u64, u64>> {
+  let parse = trace::span("parse");
   let mut monkeys: Vec<Monkey> = Vec::new();
   let mut ppcm = 1;
-  let content = std::fs::read_to_string(filename)?;
+  let content = input.read_to_string()?;
   let lines = &mut content.lines();
 
   // Monkey 0:
@@ -80,14 +85,14 @@ pub fn day11(filename: &Path) -> Result<ReturnType> {
     // Parse operation
     let line = lines.next().ok_or("Missing operation line")?;
     let cap = line_operation.captures(line).ok_or("Fail to capture operation")?;
-    let operation: Box<dyn Fn(u64, u64) -> u64> = match cap.get(1).ok_or("Fail to capture operation")?.as_str() {
-      "*" => Box::new(|a, b| a * b),
-      "+" => Box::new(|a, b| a + b),
-      "-" => Box::new(|a, b| a - b),
-      _ => panic!("Unsupported operation"),
+    let operation: Box<dyn Fn(u64, u64) -> Option<u64>> = match cap.get(1).ok_or("Fail to capture operation")?.as_str() {
+      "*" => Box::new(|a: u64, b| a.checked_mul(b)),
+      "+" => Box::new(|a: u64, b| a.checked_add(b)),
+      "-" => Box::new(|a: u64, b| a.checked_sub(b)),
+      _ => return Err("Unsupported operation".into()),
     };
     let second_term = cap.get(2).ok_or("Fail to capture second terme")?.as_str();
-    let lambda: Box<dyn Fn(u64) -> u64> = match second_term {
+    let lambda: Box<dyn Fn(u64) -> Option<u64>> = match second_term {
       "old" => Box::new(move |a| operation(a, a)),
       term => {
         let number = term.parse::<u64>()?;
@@ -98,7 +103,7 @@ pub fn day11(filename: &Path) -> Result<ReturnType> {
     let line = lines.next().ok_or("Missing test line")?;
     let cap = line_test.captures(line).ok_or("Fail to capture test")?;
     let div_test = cap.get(1).ok_or("Fail to capture test")?.as_str().parse::<u64>()?;
-    ppcm *= div_test;
+    ppcm = check_divisor(ppcm, div_test)?;
 
     let line = lines.next().ok_or("Missing true line")?;
     let cap = line_test.captures(line).ok_or("Fail to capture test true")?;
@@ -115,8 +120,11 @@ pub fn day11(filename: &Path) -> Result<ReturnType> {
       monkey_if_false,
     })
   }
+  check_targets(monkeys.iter().map(|monkey| (monkey.monkey_if_true, monkey.monkey_if_false)))?;
   let mut items_part2 = items_part1.clone();
+  parse.end();
 
+  let span = trace::span("part1");
   let mut monkey_inspection = vec![0; monkeys.len()];
   for _round in 0..20 {
     for idx in 0..monkeys.len() {
@@ -125,7 +133,7 @@ pub fn day11(filename: &Path) -> Result<ReturnType> {
       // either current_items or current_items.iter().rev() give the same result
       for item in current_items {
         monkey_inspection[idx] += 1;
-        let worry_level = (monkeys[idx].operation)(item);
+        let worry_level = (monkeys[idx].operation)(item).ok_or("Worry level overflow")?;
         let after_bored = worry_level / 3;
         if after_bored % monkeys[idx].div_test == 0 {
           let monkey_index = monkeys[idx].monkey_if_true;
@@ -141,21 +149,21 @@ pub fn day11(filename: &Path) -> Result<ReturnType> {
   monkey_inspection.sort();
   monkey_inspection.reverse();
   let part1 = monkey_inspection[0] * monkey_inspection[1];
+  span.end();
 
+  let span = trace::span("part2");
   let mut monkey_inspection = vec![0; monkeys.len()];
   for _round in 0..10000 {
     for idx in 0..monkeys.len() {
-      // take3_at_mut is two time faster than the trick we need to work around it
+      // get_many_mut is two time faster than the trick we need to work around it
       // the part1 still use the trick
-      let (current, if_true, if_false) = take3_at_mut(
+      let [current, if_true, if_false] = get_many_mut(
         &mut items_part2,
-        idx,
-        monkeys[idx].monkey_if_true,
-        monkeys[idx].monkey_if_false,
-      );
+        [idx, monkeys[idx].monkey_if_true, monkeys[idx].monkey_if_false],
+      )?;
       for item in current {
         monkey_inspection[idx] += 1;
-        let worry_level = (monkeys[idx].operation)(*item);
+        let worry_level = (monkeys[idx].operation)(*item).ok_or("Worry level overflow")?;
         let after_bored = worry_level % ppcm;
         if after_bored % monkeys[idx].div_test == 0 {
           if_true.push(after_bored);
@@ -169,8 +177,9 @@ pub fn day11(filename: &Path) -> Result<ReturnType> {
   monkey_inspection.sort();
   monkey_inspection.reverse();
   let part2 = monkey_inspection[0] * monkey_inspection[1];
+  span.end();
 
-  Ok(ReturnType::Numeric(part1, part2))
+  Ok(Solution::new(part1, part2))
 }
 
 enum Operation {
@@ -187,67 +196,66 @@ struct MonkeySpeed {
   monkey_if_false: usize,
 }
 
-pub fn day11_speed(filename: &Path) -> Result<ReturnType> {
+impl MonkeySpeed {
+  fn apply(&self, item: u64) -> Result<u64> {
+    let worry_level = match self.operation {
+      Operation::Add => item.checked_add(self.operand),
+      Operation::Mul => item.checked_mul(self.operand),
+      Operation::Square => item.checked_mul(item),
+    };
+    Ok(worry_level.ok_or("Worry level overflow")?)
+  }
+}
+
+pub fn day11_speed(input: &Input) -> Result<Solution<u64, u64>> {
+  let parse = trace::span("parse");
   let mut monkeys: Vec<MonkeySpeed> = Vec::new();
   let mut ppcm = 1;
-  let content = std::fs::read_to_string(filename)?;
-  let lines = &mut content.lines();
-
-  // Monkey 0:
-  let line_header = Regex::new(r"Monkey \d:")?;
-  //   Starting items: 79, 98
-  let line_items = Regex::new(r"((,? \d+)+)")?;
-  //   Operation: new = old * 19
-  let line_operation = Regex::new(r"new = old ([+\-*]) (old|\d+)")?;
-  //   Test: divisible by 23
-  //     If true: throw to monkey 2
-  //     If false: throw to monkey 3
-  let line_test = Regex::new(r"(\d+)")?;
+  let content = input.read_bytes()?;
+  let lines = &mut lines(&content);
 
   let mut items_part1 = Vec::new();
   while let Some(line) = lines.next() {
-    if !line_header.is_match(line) {
+    // Monkey 0:
+    if !line.starts_with(b"Monkey") {
       continue;
     }
-    // Parse items
-    let line = lines.next().ok_or("Missing items line")?;
-    let cap = line_items.captures(line).ok_or("Fail to capture")?;
-    let items = cap
-      .get(1)
-      .ok_or("Fail to capture items")?
-      .as_str()
-      .trim()
-      .split(", ")
-      .map(|elem| elem.parse::<u64>())
-      .flatten()
-      .collect::<Vec<_>>();
+    //   Starting items: 79, 98
+    let mut tokens = Tokenizer::new(lines.next().ok_or("Missing items line")?);
+    let items = std::iter::from_fn(|| tokens.next_u64()).collect::<Vec<_>>();
     items_part1.push(items);
 
-    // Parse operation
+    //   Operation: new = old * 19
     let line = lines.next().ok_or("Missing operation line")?;
-    let cap = line_operation.captures(line).ok_or("Fail to capture operation")?;
-    let operation_str = cap.get(1).ok_or("Fail to capture operation")?.as_str();
-    let second_term_str = cap.get(2).ok_or("Fail to capture second terme")?.as_str();
-    let (operation, operand): (Operation, u64) = match (operation_str, second_term_str) {
-      ("*", "old") => (Operation::Square, 0),
-      ("*", term) => (Operation::Mul, term.parse::<u64>()?),
-      ("+", term) => (Operation::Add, term.parse::<u64>()?),
-      _ => panic!("Unsupported operation"),
+    let mut tokens = Tokenizer::new(line);
+    tokens.skip_past(b'=');
+    if !tokens.skip_prefix(b" old ") {
+      return Err("Fail to capture operation".into());
+    }
+    let operation_byte = tokens.next_byte().ok_or("Fail to capture operation")?;
+    tokens.skip_byte(b' ');
+    let (operation, operand): (Operation, u64) = match (operation_byte, tokens.remaining()) {
+      (b'*', b"old") => (Operation::Square, 0),
+      (b'*', term) => (Operation::Mul, parse_u64(term)?),
+      (b'+', term) => (Operation::Add, parse_u64(term)?),
+      _ => return Err("Unsupported operation".into()),
     };
 
-    // Parse test
-    let line = lines.next().ok_or("Missing test line")?;
-    let cap = line_test.captures(line).ok_or("Fail to capture test")?;
-    let div_test = cap.get(1).ok_or("Fail to capture test")?.as_str().parse::<u64>()?;
-    ppcm *= div_test;
+    //   Test: divisible by 23
+    let div_test = Tokenizer::new(lines.next().ok_or("Missing test line")?)
+      .next_u64()
+      .ok_or("Fail to capture test")?;
+    ppcm = check_divisor(ppcm, div_test)?;
 
-    let line = lines.next().ok_or("Missing true line")?;
-    let cap = line_test.captures(line).ok_or("Fail to capture test true")?;
-    let monkey_if_true = cap.get(1).ok_or("Fail to capture test")?.as_str().parse::<usize>()?;
+    //     If true: throw to monkey 2
+    let monkey_if_true = Tokenizer::new(lines.next().ok_or("Missing true line")?)
+      .next_u64()
+      .ok_or("Fail to capture test true")? as usize;
 
-    let line = lines.next().ok_or("Missing false line")?;
-    let cap = line_test.captures(line).ok_or("Fail to capture test false")?;
-    let monkey_if_false = cap.get(1).ok_or("Fail to capture test")?.as_str().parse::<usize>()?;
+    //     If false: throw to monkey 3
+    let monkey_if_false = Tokenizer::new(lines.next().ok_or("Missing false line")?)
+      .next_u64()
+      .ok_or("Fail to capture test false")? as usize;
 
     monkeys.push(MonkeySpeed {
       operation,
@@ -257,8 +265,11 @@ pub fn day11_speed(filename: &Path) -> Result<ReturnType> {
       monkey_if_false,
     })
   }
+  check_targets(monkeys.iter().map(|monkey| (monkey.monkey_if_true, monkey.monkey_if_false)))?;
   let mut items_part2 = items_part1.clone();
+  parse.end();
 
+  let span = trace::span("part1");
   let mut monkey_inspection = vec![0; monkeys.len()];
   for _round in 0..20 {
     for idx in 0..monkeys.len() {
@@ -267,11 +278,7 @@ pub fn day11_speed(filename: &Path) -> Result<ReturnType> {
       // either current_items or current_items.iter().rev() give the same result
       monkey_inspection[idx] += current_items.len();
       for item in current_items {
-        let worry_level = match monkeys[idx].operation {
-          Operation::Add => item + monkeys[idx].operand,
-          Operation::Mul => item * monkeys[idx].operand,
-          Operation::Square => item * item,
-        };
+        let worry_level = monkeys[idx].apply(item)?;
         let after_bored = worry_level / 3;
         if after_bored % monkeys[idx].div_test == 0 {
           let monkey_index = monkeys[idx].monkey_if_true;
@@ -287,26 +294,20 @@ pub fn day11_speed(filename: &Path) -> Result<ReturnType> {
   monkey_inspection.sort();
   monkey_inspection.reverse();
   let part1 = monkey_inspection[0] * monkey_inspection[1];
+  span.end();
 
+  let span = trace::span("part2");
   let mut monkey_inspection = vec![0; monkeys.len()];
   for _round in 0..10000 {
     for (idx, monkey) in monkeys.iter().enumerate() {
-      // take3_at_mut is two time faster than the trick we need to work around it
+      // get_many_mut is two time faster than the trick we need to work around it
       // the part1 still use the trick
-      let (current, if_true, if_false) = take3_at_mut(
-        &mut items_part2,
-        idx,
-        monkey.monkey_if_true,
-        monkey.monkey_if_false,
-      );
+      let [current, if_true, if_false] =
+        get_many_mut(&mut items_part2, [idx, monkey.monkey_if_true, monkey.monkey_if_false])?;
       monkey_inspection[idx] += current.len();
       // it seem drain(..) is slower than loop+clear
       for item in &*current {
-        let worry_level = match monkey.operation {
-          Operation::Add => item + monkey.operand,
-          Operation::Mul => item * monkey.operand,
-          Operation::Square => item * item,
-        };
+        let worry_level = monkey.apply(*item)?;
         let after_bored = worry_level % ppcm;
         if after_bored % monkey.div_test == 0 {
           if_true.push(after_bored);
@@ -320,20 +321,50 @@ pub fn day11_speed(filename: &Path) -> Result<ReturnType> {
   monkey_inspection.sort();
   monkey_inspection.reverse();
   let part2 = monkey_inspection[0] * monkey_inspection[1];
+  span.end();
 
-  Ok(ReturnType::Numeric(part1 as u64, part2 as u64))
+  Ok(Solution::new(part1 as u64, part2 as u64))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
     main:   day11,        "data/day11.txt",       [117624, 16792940265];
-    test1:  day11,        "data/day11_test1.txt", [10605, 2713310158];
+    test1:  day11,        Example(EXAMPLE),       [10605, 2713310158];
     main:   day11_speed,  "data/day11.txt",       [117624, 16792940265];
-    test1:  day11_speed,  "data/day11_test1.txt", [10605, 2713310158];
+    test1:  day11_speed,  Example(EXAMPLE),       [10605, 2713310158];
   );
 }
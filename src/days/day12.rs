@@ -1,9 +1,9 @@
 // #![allow(unused_variables)]
 
-use std::collections::HashMap;
-use std::path::Path;
-
-use crate::utils::ReturnType;
+use crate::hash::FastHashMap;
+use crate::input::Input;
+use crate::utils::Solution;
+use crate::tokenizer::lines;
 use crate::Result;
 
 #[derive(Debug, Clone)]
@@ -58,38 +58,45 @@ fn get_neighbor(pos : (usize, usize), width: usize, height: usize) -> [(usize, u
   ];
 }
 
-pub fn day12(filename: &Path) -> Result<ReturnType> {
+pub fn day12(input: &Input) -> Result<Solution<u64, u64>> {
   let mut board = Board {
     data: Vec::new(),
     width: 0,
   };
-  let mut start = (0, 0);
-  let mut end = (0, 0);
-  for line in std::fs::read_to_string(filename)?.lines() {
+  let mut start = None;
+  let mut end = None;
+  for line in input.read_to_string()?.lines() {
     if board.width == 0 {
       board.width = line.chars().count();
     }
+    if line.chars().count() != board.width {
+      return Err("Lines of different width".into());
+    }
     for elevation in line.chars() {
       if elevation == 'S' {
-        start = (board.data.len() % board.width, board.data.len() / board.width);
+        start = Some((board.data.len() % board.width, board.data.len() / board.width));
         board.data.push(0);
       } else if elevation == 'E' {
-        end = (board.data.len() % board.width, board.data.len() / board.width);
+        end = Some((board.data.len() % board.width, board.data.len() / board.width));
         board.data.push(26);
-      } else {
+      } else if elevation.is_ascii_lowercase() {
         board.data.push((elevation as u32 - 'a' as u32) as u8);
+      } else {
+        return Err(format!("Invalid elevation {:?}", elevation).into());
       }
     }
   }
+  let start = start.ok_or("Missing start")?;
+  let end = end.ok_or("Missing end")?;
 
   let mut part1 = 0;
   let mut part2 = 0;
   for is_part1 in [false, true] {
     let mut frontier = Vec::new();
     frontier.push((start, 0));
-    let mut came_from = HashMap::new();
+    let mut came_from = FastHashMap::default();
     came_from.insert(start, None);
-    let mut cost_so_far = HashMap::new();
+    let mut cost_so_far = FastHashMap::default();
     cost_so_far.insert(start, 0);
 
     while let Some((current, _)) = frontier.pop() {
@@ -123,48 +130,56 @@ pub fn day12(filename: &Path) -> Result<ReturnType> {
       frontier.sort_by(|a, b| b.1.cmp(&a.1));
     }
 
-    let mut current = end;
-    let mut path = Vec::new();
-    while current != start {
-      path.push(current);
-      let Some(&Some(temp)) = came_from.get(&current) else {
-      panic!("No source found")
-    };
-      current = temp;
-    }
     if is_part1 {
+      let mut current = end;
+      let mut path = Vec::new();
+      while current != start {
+        path.push(current);
+        let Some(&Some(temp)) = came_from.get(&current) else {
+        return Err("No path found".into());
+      };
+        current = temp;
+      }
       part1 = path.len();
     } else {
+      // with the free steps on `a` the came_from links can loop, only the cost is reliable
       part2 = cost_so_far.get(&end).ok_or("No end value")? + 1;
     }
   }
-  Ok(ReturnType::Numeric(part1 as u64, part2 as u64))
+  Ok(Solution::new(part1 as u64, part2 as u64))
 }
 
 // Speed up found by starting for the end up to the start (part1) or 0 (part2)
-pub fn day12_speed(filename: &Path) -> Result<ReturnType> {
+pub fn day12_speed(input: &Input) -> Result<Solution<u64, u64>> {
   let mut board = Board {
     data: Vec::new(),
     width: 0,
   };
-  let mut start = (0, 0);
-  let mut end = (0, 0);
-  for line in std::fs::read_to_string(filename)?.lines() {
+  let mut start = None;
+  let mut end = None;
+  for line in lines(&input.read_bytes()?) {
     if board.width == 0 {
-      board.width = line.chars().count();
+      board.width = line.len();
     }
-    for elevation in line.chars() {
-      if elevation == 'S' {
-        start = (board.data.len() % board.width, board.data.len() / board.width);
+    if line.len() != board.width {
+      return Err("Lines of different width".into());
+    }
+    for &elevation in line {
+      if elevation == b'S' {
+        start = Some((board.data.len() % board.width, board.data.len() / board.width));
         board.data.push(0);
-      } else if elevation == 'E' {
-        end = (board.data.len() % board.width, board.data.len() / board.width);
+      } else if elevation == b'E' {
+        end = Some((board.data.len() % board.width, board.data.len() / board.width));
         board.data.push(26);
+      } else if elevation.is_ascii_lowercase() {
+        board.data.push(elevation - b'a');
       } else {
-        board.data.push((elevation as u32 - 'a' as u32) as u8);
+        return Err(format!("Invalid elevation {:?}", elevation as char).into());
       }
     }
   }
+  let start = start.ok_or("Missing start")?;
+  let end = end.ok_or("Missing end")?;
 
   // BFS / Dijkstra’s Algorithm
   // https://www.redblobgames.com/pathfinding/a-star/introduction.html
@@ -177,7 +192,7 @@ pub fn day12_speed(filename: &Path) -> Result<ReturnType> {
   frontier.push(end);
 
   // contain the point and the associated cost
-  let mut cost_so_far = HashMap::new();
+  let mut cost_so_far = FastHashMap::default();
   cost_so_far.insert(end, 0);
 
   while let Some(current) = frontier.pop() {
@@ -202,20 +217,30 @@ pub fn day12_speed(filename: &Path) -> Result<ReturnType> {
       break;
     }
   }
+  if part1 == 0 {
+    return Err("No path found".into());
+  }
 
-  Ok(ReturnType::Numeric(part1 as u64, part2 as u64))
+  Ok(Solution::new(part1 as u64, part2 as u64))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"Sabqponm
+abcryxxl
+accszExk
+acctuvwj
+abdefghi
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
     main:   day12,        "data/day12.txt",       [437, 430];
-    test1:  day12,        "data/day12_test1.txt", [31, 29];
+    test1:  day12,        Example(EXAMPLE),       [31, 29];
     main:   day12_speed,  "data/day12.txt",       [437, 430];
-    test1:  day12_speed,  "data/day12_test1.txt", [31, 29];
+    test1:  day12_speed,  Example(EXAMPLE),       [31, 29];
   );
 }
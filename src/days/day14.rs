@@ -2,9 +2,9 @@
 
 use itertools::Itertools;
 use itertools::MinMaxResult::{MinMax, NoElements, OneElement};
-use std::path::Path;
 
-use crate::utils::ReturnType;
+use crate::input::Input;
+use crate::utils::Solution;
 use crate::Result;
 
 #[derive(Debug, Clone)]
@@ -62,25 +62,33 @@ impl std::fmt::Display for Board {
   }
 }
 
-pub fn day14(filename: &Path) -> Result<ReturnType> {
+/// Rocks are kept in a cave small enough for the boards
+const MAX_WIDTH: i32 = 1000;
+const MAX_DEPTH: i32 = 400;
+
+pub fn day14(input: &Input) -> Result<Solution<u64, u64>> {
   let mut rock_shapes = Vec::new();
-  for line in std::fs::read_to_string(filename)?.lines() {
+  for line in input.read_to_string()?.lines() {
     rock_shapes.push(Vec::new());
     for point_str in line.split("->") {
       let mut point_split = point_str.split(",");
       let x = point_split.next().ok_or("Missing x for point")?.trim().parse::<i32>()?;
       let y = point_split.next().ok_or("Missing y for point")?.trim().parse::<i32>()?;
+      if !(1..MAX_WIDTH).contains(&x) || !(0..=MAX_DEPTH).contains(&y) {
+        return Err(format!("Rock out of the cave: {},{}", x, y).into());
+      }
       let length = rock_shapes.len();
       rock_shapes[length - 1].push((x, y));
     }
   }
-  let (min_x, max_x) = match rock_shapes.iter().flatten().map(|elem| elem.0).minmax() {
-    NoElements => panic!("NoMinMax"),
+  // the sand source is part of the board
+  let (min_x, max_x) = match rock_shapes.iter().flatten().map(|elem| elem.0).chain([500]).minmax() {
+    NoElements => return Err("NoMinMax".into()),
     OneElement(x) => (x, x),
     MinMax(x, y) => (x, y),
   };
   let (_, max_y) = match rock_shapes.iter().flatten().map(|elem| elem.1).minmax() {
-    NoElements => panic!("NoMinMax"),
+    NoElements => return Err("NoMinMax".into()),
     OneElement(x) => (x, x),
     MinMax(x, y) => (x, y),
   };
@@ -102,14 +110,17 @@ pub fn day14(filename: &Path) -> Result<ReturnType> {
 
       let step_x = (x_max - x_min).min(1);
       let step_y = (y_max - y_min).min(1);
+      if step_x != 0 && step_y != 0 {
+        return Err("Diagonal rock line".into());
+      }
       if step_x == 0 {
-        for y in (*y_min..=*y_max).step_by(step_y as usize) {
+        for y in *y_min..=*y_max {
           *board_part1.get_with_offset_mut(*x_min as usize, y as usize) = '#';
         }
       }
 
       if step_y == 0 {
-        for x in (*x_min..=*x_max).step_by(step_x as usize) {
+        for x in *x_min..=*x_max {
           *board_part1.get_with_offset_mut(x as usize, *y_min as usize) = '#';
         }
       }
@@ -122,8 +133,8 @@ pub fn day14(filename: &Path) -> Result<ReturnType> {
   rock_shapes[length - 1].push((500 - max_y - 2, max_y + 2));
   rock_shapes[length - 1].push((500 + max_y + 2, max_y + 2));
   let max_y = max_y + 2;
-  let min_x = 500 - max_y - 1;
-  let max_x = 500 + max_y + 1;
+  let min_x = min_x.min(500 - max_y - 1);
+  let max_x = max_x.max(500 + max_y + 1);
 
   let mut board_part2 = Board {
     data: vec!['.'; ((max_x - min_x + 1) * (max_y - 0 + 1)) as usize],
@@ -141,14 +152,17 @@ pub fn day14(filename: &Path) -> Result<ReturnType> {
 
       let step_x = (x_max - x_min).min(1);
       let step_y = (y_max - y_min).min(1);
+      if step_x != 0 && step_y != 0 {
+        return Err("Diagonal rock line".into());
+      }
       if step_x == 0 {
-        for y in (*y_min..=*y_max).step_by(step_y as usize) {
+        for y in *y_min..=*y_max {
           *board_part2.get_with_offset_mut(*x_min as usize, y as usize) = '#';
         }
       }
 
       if step_y == 0 {
-        for x in (*x_min..=*x_max).step_by(step_x as usize) {
+        for x in *x_min..=*x_max {
           *board_part2.get_with_offset_mut(x as usize, *y_min as usize) = '#';
         }
       }
@@ -227,17 +241,21 @@ pub fn day14(filename: &Path) -> Result<ReturnType> {
     }
   }
 
-  Ok(ReturnType::Numeric(part1 as u64, part2 as u64))
+  Ok(Solution::new(part1 as u64, part2 as u64))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"498,4 -> 498,6 -> 496,6
+503,4 -> 502,4 -> 502,9 -> 494,9
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
     main:   day14,        "data/day14.txt",       [1003, 25771];
-    test1:  day14,        "data/day14_test1.txt", [24, 93];
+    test1:  day14,        Example(EXAMPLE),       [24, 93];
   );
 }
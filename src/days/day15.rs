@@ -2,9 +2,11 @@
 
 use regex::Regex;
 use std::iter::zip;
-use std::path::Path;
 
-use crate::utils::ReturnType;
+use crate::input::Input;
+use crate::utils::Solution;
+use crate::tokenizer::{lines, Tokenizer};
+use crate::trace;
 use crate::Result;
 
 #[inline(always)]
@@ -12,60 +14,75 @@ fn manhattan(a: (i32, i32), b: (i32, i32)) -> i32 {
   (a.0 - b.0).abs() + (a.1 - b.1).abs()
 }
 
-pub fn day15(filename: &Path) -> Result<ReturnType> {
+/// Coordinates are kept small enough for distances and borders to fit in i32
+const MAX_COORDINATE: i32 = 1 << 28;
+
+fn check_coordinate(coordinate: i32) -> Result<i32> {
+  if coordinate.unsigned_abs() > MAX_COORDINATE as u32 {
+    return Err(format!("Coordinate {} too far", coordinate).into());
+  }
+  Ok(coordinate)
+}
+
+pub fn day15(input: &Input) -> Result<Solution<u64, u64>> {
+  let parse = trace::span("parse");
   let mut beacon_position = Vec::new();
   let mut sensor_position = Vec::new();
   let regex = Regex::new(r"(-?\d+)")?;
-  for line in std::fs::read_to_string(filename)?.lines() {
+  for line in input.read_to_string()?.lines() {
     let raw_position = regex
       .captures_iter(line)
       .map(|elem| elem[0].parse::<i32>())
       .flatten()
       .collect::<Vec<_>>();
 
-    let sensor = (raw_position[0], raw_position[1]);
-    let beacon = (raw_position[2], raw_position[3]);
+    let &[sx, sy, bx, by, ..] = raw_position.as_slice() else {
+      return Err("Missing coordinate".into());
+    };
+    let sensor = (check_coordinate(sx)?, check_coordinate(sy)?);
+    let beacon = (check_coordinate(bx)?, check_coordinate(by)?);
     sensor_position.push((sensor, manhattan(sensor, beacon)));
     beacon_position.push(beacon);
   }
 
   // Dirty switch as test and regular input don't have same condition
-  let line_index: i32 = if filename.to_str().unwrap().contains("test") { 10 } else { 2000000 };
-  let search_dim: i32 = if filename.to_str().unwrap().contains("test") { 20 } else { 4000000 };
+  let line_index: i32 = if input.path().to_string_lossy().contains("test") { 10 } else { 2000000 };
+  let search_dim: i32 = if input.path().to_string_lossy().contains("test") { 20 } else { 4000000 };
+  parse.end();
 
   // part1
-  let mut line_to_check = Vec::new();
+  let span = trace::span("part1");
+  // ranges of the row rather than each position, a sensor radius can be as large as the coordinates
+  let mut ranges = Vec::new();
   let mut beacon_in_line = Vec::new();
 
   for ((sensor, radius), beacon) in zip(&sensor_position, &beacon_position) {
-    if line_index - sensor.1 > *radius {
+    let half_width = radius - (line_index - sensor.1).abs();
+    if half_width < 0 {
       continue;
     }
-    for x in sensor.0 - radius..sensor.0 + radius {
-      if manhattan(*sensor, (x, line_index)) <= *radius {
-        line_to_check.push(x);
-      }
-      if beacon.1 == line_index {
-        beacon_in_line.push(beacon.0);
-      }
+    ranges.push((sensor.0 - half_width, sensor.0 + half_width));
+    if beacon.1 == line_index {
+      beacon_in_line.push(beacon.0);
     }
   }
-  line_to_check.sort();
-  line_to_check.dedup();
+  merge_ranges(&mut ranges);
   beacon_in_line.sort();
   beacon_in_line.dedup();
-  let part1 = line_to_check.len() - beacon_in_line.len();
+  let part1 = covered_length(&ranges) - beacon_in_line.len() as u64;
+  span.end();
 
   // part2
-  let mut frontier_point = Vec::new();
-  for (sensor, radius) in &sensor_position {
-    for y in (sensor.1 - radius - 1).max(0)..(sensor.1 + radius + 1).min(search_dim) {
+  let span = trace::span("part2");
+  // walked lazily, the frontier of a sensor can be millions of points long
+  let frontier_point = sensor_position.iter().flat_map(|(sensor, radius)| {
+    ((sensor.1 - radius - 1).max(0)..=(sensor.1 + radius + 1).min(search_dim)).flat_map(move |y| {
       let min_x = (sensor.0 - (radius + 1 - (sensor.1 - y).abs())).max(0);
       let max_x = (sensor.0 + (radius + 1 - (sensor.1 - y).abs())).min(search_dim);
-      frontier_point.push((min_x, y));
-      frontier_point.push((max_x, y));
-    }
-  }
+      // clamping can leave a point outside when the frontier is outside of the search area
+      [min_x, max_x].into_iter().filter(move |x| (0..=search_dim).contains(x)).map(move |x| (x, y))
+    })
+  });
   let part2 = 'block: {
     for (x, y) in frontier_point {
       let mut current = 0;
@@ -81,43 +98,57 @@ pub fn day15(filename: &Path) -> Result<ReturnType> {
     }
     0
   };
-  Ok(ReturnType::Numeric(part1 as u64, part2 as u64))
+  span.end();
+  Ok(Solution::new(part1, part2 as u64))
 }
 
-/// Given two ranges return 1 or two non overlaping range
-fn merge_range(range1:(i32, i32), range2:(i32, i32)) -> ((i32, i32), (i32, i32))
-{
-  //   range1     0-------1
-  //   range2              0-----------1
-  if range1.1 < range2.0 - 1 || range2.1 < range1.0 - 1 {
-    (range1, range2)
-  } else {
-    ((range1.0.min(range2.0), range1.1.max(range2.1)), (0, 0))
+/// Sort ranges and merge the overlapping or adjacent ones
+/// Merging pairs in a fixed number of passes could leave ranges unmerged.
+fn merge_ranges(ranges: &mut Vec<(i32, i32)>) {
+  ranges.sort_unstable();
+  let mut merged: Vec<(i32, i32)> = Vec::with_capacity(ranges.len());
+  for &(start, end) in ranges.iter() {
+    match merged.last_mut() {
+      Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+      _ => merged.push((start, end)),
+    }
   }
+  *ranges = merged;
 }
 
-pub fn day15_only_range(filename: &Path) -> Result<ReturnType> {
+/// Number of positions covered by merged ranges, the row can have several disjoint ranges
+fn covered_length(ranges: &[(i32, i32)]) -> u64 {
+  ranges.iter().map(|(start, end)| (end - start + 1) as u64).sum()
+}
+
+pub fn day15_only_range(input: &Input) -> Result<Solution<u64, u64>> {
+  let parse = trace::span("parse");
   let mut beacon_position = Vec::new();
   let mut sensor_position = Vec::new();
   let regex = Regex::new(r"(-?\d+)")?;
-  for line in std::fs::read_to_string(filename)?.lines() {
+  for line in input.read_to_string()?.lines() {
     let raw_position = regex
       .captures_iter(line)
       .map(|elem| elem[0].parse::<i32>())
       .flatten()
       .collect::<Vec<_>>();
 
-    let sensor = (raw_position[0], raw_position[1]);
-    let beacon = (raw_position[2], raw_position[3]);
+    let &[sx, sy, bx, by, ..] = raw_position.as_slice() else {
+      return Err("Missing coordinate".into());
+    };
+    let sensor = (check_coordinate(sx)?, check_coordinate(sy)?);
+    let beacon = (check_coordinate(bx)?, check_coordinate(by)?);
     sensor_position.push((sensor, manhattan(sensor, beacon)));
     beacon_position.push(beacon);
   }
 
   // Dirty switch as test and regular input don't have same condition
-  let line_index: i32 = if filename.to_str().unwrap().contains("test") { 10 } else { 2000000 };
-  let search_dim: i32 = if filename.to_str().unwrap().contains("test") { 20 } else { 4000000 };
+  let line_index: i32 = if input.path().to_string_lossy().contains("test") { 10 } else { 2000000 };
+  let search_dim: i32 = if input.path().to_string_lossy().contains("test") { 20 } else { 4000000 };
+  parse.end();
 
   // part1
+  let span = trace::span("part1");
   let mut ranges = Vec::new();
   let mut beacon_in_line = Vec::new();
   for ((sensor, radius), beacon) in zip(&sensor_position, &beacon_position) {
@@ -132,24 +163,18 @@ pub fn day15_only_range(filename: &Path) -> Result<ReturnType> {
     assert!(min_x <= max_x);
     ranges.push((min_x, max_x));
   }
-  // Merge stored range directly
-  for _loop in 0..2 {
-    for idx1 in 0..ranges.len()-1 {
-      for idx2 in idx1+1..ranges.len() {
-        let (range1, range2) = merge_range(ranges[idx1], ranges[idx2]);
-        ranges[idx1] = range1;
-        ranges[idx2] = range2;
-      }
-    }
-  }
+  merge_ranges(&mut ranges);
   beacon_in_line.sort();
   beacon_in_line.dedup();
-  let part1 = (ranges[0].1 - ranges[0].0 + 1) as u64 - beacon_in_line.len() as u64;
+  let part1 = covered_length(&ranges) - beacon_in_line.len() as u64;
+  span.end();
 
+  let span = trace::span("part2");
   // part2.2
   let part2 = 'block: {
-    for y in 0..search_dim {
-      let mut ranges = Vec::new();
+    let mut ranges = Vec::new();
+    for y in 0..=search_dim {
+      ranges.clear();
       for (sensor, radius) in &sensor_position {
         if (y - sensor.1).abs() > *radius {
           continue;
@@ -157,58 +182,49 @@ pub fn day15_only_range(filename: &Path) -> Result<ReturnType> {
         let min_x = sensor.0 - (radius - (sensor.1 - y).abs()).abs();
         let max_x = sensor.0 + (radius - (sensor.1 - y).abs()).abs();
         assert!(min_x <= max_x);
-        let mut new_range = (min_x, max_x);
-        for idx1 in 0..ranges.len() {
-          let (range1, range2) = merge_range(ranges[idx1], new_range);
-          ranges[idx1] = range1;
-          new_range = range2;
-        }
-        if new_range != (0, 0) {
-          ranges.push(new_range);
-        }
+        ranges.push((min_x, max_x));
       }
-      // Merge stored range directly
-      // Even if I merge range on the fly I still need this  second pass
-      for _loop in 0..2 {
-        for idx2 in 1..ranges.len() {
-          let (range1, range2) = merge_range(ranges[0], ranges[idx2]);
-          ranges[0] = range1;
-          ranges[idx2] = range2;
+      merge_ranges(&mut ranges);
+      // First position of the search area after the ranges
+      let mut x = 0;
+      for (start, end) in &ranges {
+        if *start > x {
+          break;
         }
+        x = x.max(end + 1);
       }
-      let part1 = ranges[0].1.min(search_dim-1) - ranges[0].0.max(0) + 1;
-      if part1 != search_dim {
-        break 'block (ranges[0].1 + 1) as u64 * 4000000 + y as u64;
+      if x <= search_dim {
+        break 'block x as u64 * 4000000 + y as u64;
       }
     }
     0
   };
+  span.end();
 
-  Ok(ReturnType::Numeric(part1 as u64, part2 as u64))
+  Ok(Solution::new(part1 as u64, part2 as u64))
 }
 
-pub fn day15_speed(filename: &Path) -> Result<ReturnType> {
+pub fn day15_speed(input: &Input) -> Result<Solution<u64, u64>> {
+  let parse = trace::span("parse");
   let mut beacon_position = Vec::new();
   let mut sensor_position = Vec::new();
-  let regex = Regex::new(r"(-?\d+)")?;
-  for line in std::fs::read_to_string(filename)?.lines() {
-    let raw_position = regex
-      .captures_iter(line)
-      .map(|elem| elem[0].parse::<i32>())
-      .flatten()
-      .collect::<Vec<_>>();
-
-    let sensor = (raw_position[0], raw_position[1]);
-    let beacon = (raw_position[2], raw_position[3]);
+  for line in lines(&input.read_bytes()?) {
+    // Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+    let mut tokens = Tokenizer::new(line);
+    let mut next_coordinate = || -> Result<i32> { check_coordinate(tokens.next_i64().ok_or("Missing coordinate")?.try_into()?) };
+    let sensor = (next_coordinate()?, next_coordinate()?);
+    let beacon = (next_coordinate()?, next_coordinate()?);
     sensor_position.push((sensor, manhattan(sensor, beacon)));
     beacon_position.push(beacon);
   }
 
   // Dirty switch as test and regular input don't have same condition
-  let line_index: i32 = if filename.to_str().unwrap().contains("test") { 10 } else { 2000000 };
-  let search_dim: i32 = if filename.to_str().unwrap().contains("test") { 20 } else { 4000000 };
+  let line_index: i32 = if input.path().to_string_lossy().contains("test") { 10 } else { 2000000 };
+  let search_dim: i32 = if input.path().to_string_lossy().contains("test") { 20 } else { 4000000 };
+  parse.end();
 
   // part1
+  let span = trace::span("part1");
   // For part1 we directly merge range
   let mut ranges = Vec::new();
   let mut beacon_in_line = Vec::new();
@@ -224,19 +240,13 @@ pub fn day15_speed(filename: &Path) -> Result<ReturnType> {
     assert!(min_x <= max_x);
     ranges.push((min_x, max_x));
   }
-  // Merge stored range directly
-  // We have to do several iteration to be sure we merge all possible case
-  for _loop in 0..2 {
-    for idx2 in 1..ranges.len() {
-      let (range1, range2) = merge_range(ranges[0], ranges[idx2]);
-      ranges[0] = range1;
-      ranges[idx2] = range2;
-    }
-  }
+  merge_ranges(&mut ranges);
   beacon_in_line.sort();
   beacon_in_line.dedup();
-  let part1 = (ranges[0].1 - ranges[0].0 + 1) as u64 - beacon_in_line.len() as u64;
+  let part1 = covered_length(&ranges) - beacon_in_line.len() as u64;
+  span.end();
 
+  let span = trace::span("part2");
   // part2
   // for each sensor
   //   for each pixel on the border of the sensor
@@ -247,7 +257,7 @@ pub fn day15_speed(filename: &Path) -> Result<ReturnType> {
   //        we have the result
   let part2 = 'block: {
     for (sensor, radius) in &sensor_position {
-      for y in (sensor.1 - radius - 1).max(0)..(sensor.1 + radius + 1).min(search_dim) {
+      for y in (sensor.1 - radius - 1).max(0)..=(sensor.1 + radius + 1).min(search_dim) {
         let min_x = (sensor.0 - (radius + 1 - (sensor.1 - y).abs())).max(0);
         let max_x = (sensor.0 + (radius + 1 - (sensor.1 - y).abs())).min(search_dim);
         // min_x
@@ -258,7 +268,7 @@ pub fn day15_speed(filename: &Path) -> Result<ReturnType> {
             break;
           }
         }
-        if current == 0 {
+        if current == 0 && min_x <= search_dim {
           break 'block min_x as u64 * 4000000 + y as u64;
         }
         // max_x
@@ -269,7 +279,7 @@ pub fn day15_speed(filename: &Path) -> Result<ReturnType> {
             break;
           }
         }
-        if current == 0 {
+        if current == 0 && max_x >= 0 {
           break 'block max_x as u64 * 4000000 + y as u64;
         }
 
@@ -277,21 +287,38 @@ pub fn day15_speed(filename: &Path) -> Result<ReturnType> {
     }
     0
   };
-  Ok(ReturnType::Numeric(part1 as u64, part2 as u64))
+  span.end();
+  Ok(Solution::new(part1 as u64, part2 as u64))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
     main:   day15,            "data/day15.txt",       [5525990, 11756174628223];
-    test1:  day15,            "data/day15_test1.txt", [26, 56000011];
+    test1:  day15,            Example(EXAMPLE),       [26, 56000011];
     main:   day15_speed,      "data/day15.txt",       [5525990, 11756174628223];
-    test1:  day15_speed,      "data/day15_test1.txt", [26, 56000011];
+    test1:  day15_speed,      Example(EXAMPLE),       [26, 56000011];
     main:   day15_only_range, "data/day15.txt",       [5525990, 11756174628223];
-    test1:  day15_only_range, "data/day15_test1.txt", [26, 56000011];
+    test1:  day15_only_range, Example(EXAMPLE),       [26, 56000011];
   );
 }
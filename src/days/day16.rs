@@ -1,89 +1,110 @@
 // #![allow(unused_variables)]
 
 use regex::Regex;
-use std::collections::HashMap;
-use std::path::Path;
 
-use crate::utils::ReturnType;
+use crate::input::Input;
+use crate::memo::{pack, BitSet64, Memo};
+use crate::trace;
+use crate::utils::Solution;
 use crate::Result;
 
+/// Graph reduced to the valves with a flow
+struct Network {
+  /// flow of each valve with a flow
+  flow: Vec<i32>,
+  /// distance between valves with a flow
+  distance: Vec<Vec<i32>>,
+  /// distance from the starting valve AA to valves with a flow
+  distance_from_start: Vec<i32>,
+}
+
+/// Cache key of the search : (current valve, remaining time, remaining valves)
+/// current valve is `flow.len()` for the starting valve
+fn cache_key(valve_index: usize, remaining_time: i32, remaining_valve: BitSet64) -> u64 {
+  pack([
+    (valve_index as u64, 8),
+    (remaining_time as u64, 8),
+    (remaining_valve.0, 48),
+  ])
+}
+
+fn distance(network: &Network, valve_index: usize, next: usize) -> i32 {
+  if valve_index == network.flow.len() {
+    network.distance_from_start[next]
+  } else {
+    network.distance[valve_index][next]
+  }
+}
+
 fn dfs(
   valve_index: usize,
   remaining_time: i32,
-  remaning_valve: Vec<usize>,
-  valve_flow: &Vec<i32>,
-  adjacent_matrix: &Vec<Vec<i32>>,
-  cache: &mut HashMap<(usize, i32, Vec<usize>), i32>,
+  remaining_valve: BitSet64,
+  network: &Network,
+  cache: &mut Memo<u64, i32>,
 ) -> i32 {
-  let cache_key = (valve_index, remaining_time, remaning_valve.clone());
-  if let Some(cached_value) = cache.get(&cache_key) {
-    return *cached_value;
-  }
-
-  let mut score = 0;
-  for idx in 0..remaning_valve.len() {
-    let next = remaning_valve[idx];
-    if adjacent_matrix[valve_index][next] >= remaining_time {
-      continue;
+  cache.compute(cache_key(valve_index, remaining_time, remaining_valve), |cache| {
+    let mut score = 0;
+    for next in remaining_valve.iter() {
+      let distance = distance(network, valve_index, next);
+      if distance >= remaining_time {
+        continue;
+      }
+      let time = remaining_time - distance - 1;
+      score = score.max(network.flow[next] * time + dfs(next, time, remaining_valve.without(next), network, cache));
     }
-    let mut remain = remaning_valve.clone();
-    remain.remove(idx);
-    let time = remaining_time - adjacent_matrix[valve_index][next] - 1;
-    score = score.max(
-      valve_flow[next] * (remaining_time - adjacent_matrix[valve_index][next] - 1)
-        + dfs(next, time, remain, valve_flow, adjacent_matrix, cache),
-    );
-  }
-  cache.insert(cache_key, score);
-  score
+    score
+  })
 }
 
 // caching part2 result is negligible because we still use caching of dfs
 fn dfs2(
   valve_index: usize,
   remaining_time: i32,
-  remaning_valve: Vec<usize>,
-  aa_index: usize,
-  valve_flow: &Vec<i32>,
-  adjacent_matrix: &Vec<Vec<i32>>,
-  cache1: &mut HashMap<(usize, i32, Vec<usize>), i32>,
-  cache2: &mut HashMap<(usize, i32, Vec<usize>), i32>,
+  remaining_valve: BitSet64,
+  network: &Network,
+  cache1: &mut Memo<u64, i32>,
+  cache2: &mut Memo<u64, i32>,
 ) -> i32 {
-
-  let cache_key = (valve_index, remaining_time, remaning_valve.clone());
-  if let Some(cached_value) = cache2.get(&cache_key) {
-    return *cached_value;
+  if let Some(cached_value) = cache2.get(&cache_key(valve_index, remaining_time, remaining_valve)) {
+    return cached_value;
   }
 
   let mut score = 0;
-  for idx in 0..remaning_valve.len() {
-    let next = remaning_valve[idx];
-    if adjacent_matrix[valve_index][next] >= remaining_time {
+  for next in remaining_valve.iter() {
+    let distance = distance(network, valve_index, next);
+    if distance >= remaining_time {
       continue;
     }
-    let mut remain = remaning_valve.clone();
-    remain.remove(idx);
-    let time = remaining_time - adjacent_matrix[valve_index][next] - 1;
-    score = score.max(valve_flow[next] * (remaining_time - adjacent_matrix[valve_index][next] - 1)
-                    + dfs2(next, time, remain, aa_index, valve_flow, adjacent_matrix, cache1, cache2));
+    let time = remaining_time - distance - 1;
+    score = score.max(
+      network.flow[next] * time + dfs2(next, time, remaining_valve.without(next), network, cache1, cache2),
+    );
   }
 
-  let temp_score = dfs(aa_index, 26, remaning_valve.clone(), valve_flow, adjacent_matrix, cache1);
-  cache2.insert(cache_key, i32::max(temp_score, score));
+  let temp_score = dfs(network.flow.len(), 26, remaining_valve, network, cache1);
+  cache2.insert(cache_key(valve_index, remaining_time, remaining_valve), i32::max(temp_score, score));
   i32::max(temp_score, score)
 }
 
+const MAX_FLOW_RATE: i32 = 100_000;
+
+/// Valve index and remaining valves are packed in the cache key
+pub(crate) const MAX_VALVES: usize = 255;
+pub(crate) const MAX_VALVES_WITH_FLOW: usize = 48;
+
 // For part1 my solution was wrong only on the real input, I was on off by 1 and haven't found why
 // Here is a translation in rust of https://github.com/betaveros/advent-of-code-2022/blob/main/p16.noul
 // that give the right result
-pub fn day16(filename: &Path) -> Result<ReturnType> {
+pub fn day16(input: &Input) -> Result<Solution<u64, u64>> {
+  let parse = trace::span("parse");
   let mut valve_index = Vec::new();
   let mut valve_connection = Vec::new();
   let mut valve_flow = Vec::new();
 
   // Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
   let re = Regex::new(r"([A-Z]{2}).*=(\d+).+?((?:,? [A-Z]{2})+)")?;
-  let content = std::fs::read_to_string(filename)?;
+  let content = input.read_to_string()?;
   for line in content.lines() {
     let caps = re.captures(line).ok_or("Fail to capture")?;
     let current_valve = caps.get(1).ok_or("Fail to capture valve id")?.as_str();
@@ -92,6 +113,10 @@ pub fn day16(filename: &Path) -> Result<ReturnType> {
       .ok_or("Fail to capture flow rate")?
       .as_str()
       .parse::<i32>()?;
+    // pressures of both parts are summed in i32
+    if flow_rate > MAX_FLOW_RATE {
+      return Err(format!("Flow rate {} too high", flow_rate).into());
+    }
     let next_valve = caps
       .get(3)
       .ok_or("Fail to capture next valves id")?
@@ -108,7 +133,8 @@ pub fn day16(filename: &Path) -> Result<ReturnType> {
   let mut adjacent_matrix = vec![vec![99; valve_index.len()]; valve_index.len()];
   for (idx, connections) in valve_connection.iter().enumerate() {
     for connection in connections {
-      adjacent_matrix[idx][valve_index.iter().position(|e| e == connection).unwrap()] = 1;
+      let connection_index = valve_index.iter().position(|e| e == connection).ok_or("Tunnel to an unknown valve")?;
+      adjacent_matrix[idx][connection_index] = 1;
     }
   }
 
@@ -126,40 +152,54 @@ pub fn day16(filename: &Path) -> Result<ReturnType> {
     .enumerate()
     .filter_map(|(i, &f)| if f > 0 { Some(i) } else { None })
     .collect::<Vec<_>>();
+  if valve_with_flow.len() > MAX_VALVES_WITH_FLOW || valve_index.len() > MAX_VALVES {
+    return Err("Too many valves".into());
+  }
 
-  let mut cache1 = HashMap::new();
-  let aa_index = valve_index.iter().position(|&e| e == "AA").unwrap();
-  let part1 = dfs(
-    aa_index,
-    30,
-    valve_with_flow.clone(),
-    &valve_flow,
-    &adjacent_matrix,
-    &mut cache1,
-  );
-  let mut cache2 = HashMap::new();
-  let part2 = dfs2(
-    aa_index,
-    26,
-    valve_with_flow,
-    aa_index,
-    &valve_flow,
-    &adjacent_matrix,
-    &mut cache1,
-    &mut cache2
-  );
-
-  Ok(ReturnType::Numeric(part1 as u64, part2 as u64))
+  let aa_index = valve_index.iter().position(|&e| e == "AA").ok_or("No valve AA")?;
+  let network = Network {
+    flow: valve_with_flow.iter().map(|&idx| valve_flow[idx]).collect(),
+    distance: valve_with_flow
+      .iter()
+      .map(|&from| valve_with_flow.iter().map(|&to| adjacent_matrix[from][to]).collect())
+      .collect(),
+    distance_from_start: valve_with_flow.iter().map(|&to| adjacent_matrix[aa_index][to]).collect(),
+  };
+  let all_valves = BitSet64::full(valve_with_flow.len());
+  parse.end();
+
+  let span = trace::span("part1");
+  let mut cache1 = Memo::new();
+  let part1 = dfs(network.flow.len(), 30, all_valves, &network, &mut cache1);
+  span.end();
+  let span = trace::span("part2");
+  let mut cache2 = Memo::new();
+  let part2 = dfs2(network.flow.len(), 26, all_valves, &network, &mut cache1, &mut cache2);
+  span.end();
+
+  Ok(Solution::new(part1 as u64, part2 as u64))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+Valve EE has flow rate=3; tunnels lead to valves FF, DD
+Valve FF has flow rate=0; tunnels lead to valves EE, GG
+Valve GG has flow rate=0; tunnels lead to valves FF, HH
+Valve HH has flow rate=22; tunnel leads to valve GG
+Valve II has flow rate=0; tunnels lead to valves AA, JJ
+Valve JJ has flow rate=21; tunnel leads to valve II
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
     main:   day16,        "data/day16.txt",       [1754, 2474];
-    test1:  day16,        "data/day16_test1.txt", [24, 1707];
+    test1:  day16,        Example(EXAMPLE),       [1651, 1707];
   );
 }
@@ -1,9 +1,9 @@
 // #![allow(unused_variables)]
 // #![allow(unused_imports)]
 
-use std::path::Path;
 
-use crate::utils::ReturnType;
+use crate::input::Input;
+use crate::utils::{Solution, Unsolved};
 use crate::Result;
 
 #[derive(Debug, Clone)]
@@ -38,11 +38,14 @@ impl std::fmt::Display for Board {
 }
 
 
-pub fn day17(filename: &Path) -> Result<ReturnType> {
+pub fn day17(input: &Input) -> Result<Solution<u64, Unsolved>> {
   let mut movements = Vec::new();
-  for line in std::fs::read_to_string(filename)?.lines() {
+  for line in input.read_to_string()?.lines() {
     movements = line.chars().collect::<Vec<_>>();
   }
+  if movements.is_empty() || movements.iter().any(|movement| !matches!(movement, '<' | '>')) {
+    return Err("Jets are only < and >".into());
+  }
 
   // Fill Board Part1
   let width = 7;
@@ -98,11 +101,7 @@ pub fn day17(filename: &Path) -> Result<ReturnType> {
           }
           _ => unreachable!(),
         }
-        count = if count > movements.len() - 2 {
-          0
-        } else {
-          count + 1
-        };
+        count = (count + 1) % movements.len();
 
         // stabilize
         let stabilized = 'stabilize_ckeck: {
@@ -118,26 +117,29 @@ pub fn day17(filename: &Path) -> Result<ReturnType> {
           for rock in &rocks {
             *board_part1.get_mut(rock.0, rock.1) = '#';
           }
-          start_offset.1 = *rocks.iter().map(|(_x, y)| y).max().unwrap() + 5;
+          start_offset.1 = start_offset.1.max(*rocks.iter().map(|(_x, y)| y).max().unwrap() + 5);
           break;
         }
       }
     }
     // println!("{}", board_part1);
-    start_offset.1 - 5
+    start_offset.1 - 4
   };
 
-  Ok(ReturnType::Numeric(part1 as u64, 2 as u64))
+  Ok(Solution::new(part1 as u64, Unsolved))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
-    main:   day17,        "data/day17.txt",       [1003, 25771];
-    test1:  day17,        "data/day17_test1.txt", [24, 93];
+    main:   day17,        "data/day17.txt",       [Unsolved, Unsolved];
+    test1:  day17,        Example(EXAMPLE),       [3068, Unsolved];
   );
 }
@@ -1,37 +1,59 @@
 // #![allow(unused_variables)]
 // #![allow(unused_imports)]
 
-use std::collections::HashSet;
-use std::path::Path;
-
-use crate::utils::ReturnType;
+use crate::hash::FastHashSet;
+use crate::input::Input;
+use crate::utils::{Solution, Unsolved};
 use crate::Result;
 
-pub fn day18(filename: &Path) -> Result<ReturnType> {
-  let mut cube_map = HashSet::new();
-  let content =  std::fs::read_to_string(filename)?;
+const MAX_COORDINATE: i32 = 1 << 20;
+
+pub fn day18(input: &Input) -> Result<Solution<u64, Unsolved>> {
+  let mut cube_map = FastHashSet::default();
+  let content =  input.read_to_string()?;
   for line in content.lines() {
     let coordinate = line.split(',').map(|e| e.parse::<i32>()).flatten().collect::<Vec<_>>();
-    cube_map.insert((coordinate[0], coordinate[1], coordinate[2]));
+    let &[x, y, z, ..] = coordinate.as_slice() else {
+      return Err(format!("Cube needs 3 coordinates : {}", line).into());
+    };
+    // neighbors are computed without overflow check
+    if [x, y, z].iter().any(|c| c.unsigned_abs() > MAX_COORDINATE as u32) {
+      return Err(format!("Cube too far : {}", line).into());
+    }
+    cube_map.insert((x, y, z));
   }
 
-  println!("{:?}", cube_map);
   let mut part1 = 0;
   for (x, y, z) in &cube_map {
     let neighbor = [(x+1,*y,*z), (x-1,*y,*z), (*x,y+1,*z), (*x,y-1,*z), (*x,*y,z+1), (*x,*y,z-1)];
     part1 += neighbor.iter().filter(|&e| !cube_map.contains(e)).count();
   }
-  Ok(ReturnType::Numeric(part1 as u64, 2 as u64))
+  Ok(Solution::new(part1 as u64, Unsolved))
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::test_helper::add_test;
+  use crate::test_helper::{add_test, Example};
+
+  const EXAMPLE: &str = r"2,2,2
+1,2,2
+3,2,2
+2,1,2
+2,3,2
+2,2,1
+2,2,3
+2,2,4
+2,2,6
+1,2,5
+3,2,5
+2,1,5
+2,3,5
+";
 
   #[rustfmt::skip::macros(add_test)]
   add_test!(
-    main:   day18,        "data/day18.txt",       [3498, 3];
-    test1:  day18,        "data/day18_test1.txt", [64, 3];
+    main:   day18,        "data/day18.txt",       [3498, Unsolved];
+    test1:  day18,        Example(EXAMPLE),       [64, Unsolved];
   );
 }
@@ -0,0 +1,380 @@
+//! Determinism: a solver must give the same answer whatever the iteration order of its maps and
+//! whatever the order of the parts of the input that the puzzle doesn't care about.
+//! Each variant is run several times with a different hasher seed, and on equivalent inputs
+//! (shuffled lines, mirrored grids, ...) for the days where such a transformation exists.
+use std::fmt;
+
+use crate::generate;
+use crate::hash;
+use crate::input::Input;
+use crate::property::{self, Outcome};
+use crate::registry::{self, Solver};
+use crate::rng::Rng;
+use crate::Result;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+  /// Number of generated inputs per day
+  pub inputs: u32,
+  /// Runs of each variant on each input, the first one is the reference
+  pub runs: u32,
+  pub seed: u64,
+  /// Size of the generated inputs, the day default size if missing
+  pub size: Option<usize>,
+}
+
+impl Default for Config {
+  fn default() -> Self {
+    Self {
+      inputs: 5,
+      runs: 10,
+      seed: 0,
+      size: None,
+    }
+  }
+}
+
+/// Lines of `content` put back together, keeping the final new line
+fn join<S: AsRef<str>>(lines: &[S], content: &str) -> String {
+  let mut out = lines.iter().map(AsRef::as_ref).collect::<Vec<_>>().join("\n");
+  if content.ends_with('\n') {
+    out.push('\n');
+  }
+  out
+}
+
+fn shuffle_lines(rng: &mut Rng, content: &str) -> String {
+  let mut lines = content.lines().collect::<Vec<_>>();
+  rng.shuffle(&mut lines);
+  join(&lines, content)
+}
+
+/// Mirror a grid horizontally and/or vertically
+fn flip_grid(rng: &mut Rng, content: &str) -> String {
+  let mut lines = content.lines().map(String::from).collect::<Vec<_>>();
+  if rng.chance(1, 2) {
+    lines = lines.iter().map(|line| line.chars().rev().collect()).collect();
+  }
+  if rng.chance(1, 2) {
+    lines.reverse();
+  }
+  join(&lines, content)
+}
+
+/// Input giving the same answers as `content`, None when the day has no such transformation
+pub fn equivalent(day: u32, rng: &mut Rng, content: &str) -> Option<String> {
+  match day {
+    // elves order
+    1 => {
+      let mut elves = content.trim_end().split("\n\n").collect::<Vec<_>>();
+      rng.shuffle(&mut elves);
+      Some(elves.join("\n\n") + "\n")
+    }
+    2 | 15 => Some(shuffle_lines(rng, content)),
+    // groups order, elves order in a group and items order in a compartment
+    3 => {
+      let lines = content.lines().collect::<Vec<_>>();
+      let mut groups = lines
+        .chunks(3)
+        .map(|group| {
+          let mut group = group
+            .iter()
+            .map(|line| {
+              let (first, second) = line.split_at(line.len() / 2);
+              let mut first = first.chars().collect::<Vec<_>>();
+              let mut second = second.chars().collect::<Vec<_>>();
+              rng.shuffle(&mut first);
+              rng.shuffle(&mut second);
+              first.into_iter().chain(second).collect::<String>()
+            })
+            .collect::<Vec<_>>();
+          rng.shuffle(&mut group);
+          group
+        })
+        .collect::<Vec<_>>();
+      // an incomplete last group has to stay last
+      let complete = lines.len() / 3;
+      rng.shuffle(&mut groups[..complete]);
+      let lines = groups.concat();
+      Some(join(&lines, content))
+    }
+    // pairs order and elves order in a pair
+    4 => {
+      let mut lines = content
+        .lines()
+        .map(|line| match line.split_once(',') {
+          Some((first, second)) if rng.chance(1, 2) => format!("{},{}", second, first),
+          _ => line.to_string(),
+        })
+        .collect::<Vec<_>>();
+      rng.shuffle(&mut lines);
+      Some(join(&lines, content))
+    }
+    // entries of a directory listing
+    7 => {
+      let mut lines = content.lines().collect::<Vec<_>>();
+      let mut start = 0;
+      while start < lines.len() {
+        let end = (start..lines.len())
+          .find(|index| lines[*index].starts_with('$'))
+          .unwrap_or(lines.len());
+        rng.shuffle(&mut lines[start..end]);
+        start = end + 1;
+      }
+      Some(join(&lines, content))
+    }
+    8 | 12 => Some(flip_grid(rng, content)),
+    // the rope rules are the same in a mirror or along the diagonal
+    9 => {
+      let mut directions = ['U', 'D', 'L', 'R'];
+      if rng.chance(1, 2) {
+        directions.swap(0, 1);
+      }
+      if rng.chance(1, 2) {
+        directions.swap(2, 3);
+      }
+      if rng.chance(1, 2) {
+        directions = [directions[2], directions[3], directions[0], directions[1]];
+      }
+      let map = |c: char| match c {
+        'U' => directions[0],
+        'D' => directions[1],
+        'L' => directions[2],
+        'R' => directions[3],
+        _ => c,
+      };
+      Some(content.chars().map(map).collect())
+    }
+    // items of a monkey are inspected in order but each one on its own
+    11 => {
+      let lines = content
+        .lines()
+        .map(|line| match line.split_once("Starting items: ") {
+          Some((indent, items)) => {
+            let mut items = items.split(", ").collect::<Vec<_>>();
+            rng.shuffle(&mut items);
+            format!("{}Starting items: {}", indent, items.join(", "))
+          }
+          None => line.to_string(),
+        })
+        .collect::<Vec<_>>();
+      Some(join(&lines, content))
+    }
+    // rock paths order and direction
+    14 => {
+      let mut lines = content
+        .lines()
+        .map(|line| {
+          let mut points = line.split(" -> ").collect::<Vec<_>>();
+          if rng.chance(1, 2) {
+            points.reverse();
+          }
+          points.join(" -> ")
+        })
+        .collect::<Vec<_>>();
+      rng.shuffle(&mut lines);
+      Some(join(&lines, content))
+    }
+    // valves order and tunnels order
+    16 => {
+      let mut lines = content
+        .lines()
+        .map(|line| {
+          // tunnels follow the last `valve` or `valves` word
+          let start = line
+            .rfind("valve")
+            .and_then(|index| line[index..].find(' ').map(|space| index + space + 1));
+          match start {
+            Some(start) => {
+              let mut tunnels = line[start..].split(", ").collect::<Vec<_>>();
+              rng.shuffle(&mut tunnels);
+              format!("{}{}", &line[..start], tunnels.join(", "))
+            }
+            None => line.to_string(),
+          }
+        })
+        .collect::<Vec<_>>();
+      rng.shuffle(&mut lines);
+      Some(join(&lines, content))
+    }
+    // cubes order and axes order
+    18 => {
+      let mut axes = [0, 1, 2];
+      rng.shuffle(&mut axes);
+      let mut lines = content
+        .lines()
+        .map(|line| {
+          let coordinates = line.split(',').collect::<Vec<_>>();
+          match coordinates.as_slice() {
+            [_, _, _] => axes.map(|axis| coordinates[axis]).join(","),
+            _ => line.to_string(),
+          }
+        })
+        .collect::<Vec<_>>();
+      rng.shuffle(&mut lines);
+      Some(join(&lines, content))
+    }
+    _ => None,
+  }
+}
+
+/// Same behavior: same answer, or both reject the input whatever the message
+fn same(reference: &Outcome, other: &Outcome) -> bool {
+  match (reference, other) {
+    (Outcome::Error(_), Outcome::Error(_)) => true,
+    _ => reference == other,
+  }
+}
+
+/// Run of a variant that didn't give the reference answer
+#[derive(Debug, Clone)]
+pub struct Finding {
+  pub day: u32,
+  pub variant: &'static str,
+  /// Generator seed and size of the input
+  pub seed: u64,
+  pub size: usize,
+  pub hash_seed: u64,
+  /// Equivalent input the variant ran on, the generated one if missing
+  pub equivalent: Option<String>,
+  pub expected: Outcome,
+  pub actual: Outcome,
+}
+
+impl fmt::Display for Finding {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    writeln!(
+      f,
+      "{} is not deterministic (seed={} size={} hash seed={:#x})",
+      self.variant, self.seed, self.size, self.hash_seed
+    )?;
+    writeln!(f, "  expected {}", self.expected)?;
+    writeln!(f, "  got      {}", self.actual)?;
+    writeln!(
+      f,
+      "generated input: generate {} --seed {} --size {} --example",
+      self.day, self.seed, self.size
+    )?;
+    match &self.equivalent {
+      Some(equivalent) => write!(f, "equivalent input:\n{}", equivalent),
+      None => write!(f, "on the generated input itself"),
+    }
+  }
+}
+
+/// Run `solver` with the hashers of this thread starting from `hash_seed`
+fn run_seeded(solver: Solver, input: &Input, hash_seed: u64) -> Outcome {
+  hash::set_seed(hash_seed);
+  let outcome = property::run(solver, input);
+  hash::set_seed(0);
+  outcome
+}
+
+/// Run every variant of `day` `config.runs` times on each generated input.
+/// Odd runs use the generated input, even runs an equivalent one when the day has one.
+pub fn check_day(day: u32, config: &Config) -> Result<Vec<Finding>> {
+  let variants = registry::variants(day);
+  let size = config
+    .size
+    .or_else(|| generate::default_size(day))
+    .ok_or(format!("No generator for day {}", day))?;
+  let mut rng = Rng::new(config.seed ^ day as u64);
+  let mut findings = Vec::new();
+  for _ in 0..config.inputs {
+    let seed = rng.next_u64();
+    let options = generate::Options {
+      seed,
+      size: Some(size),
+      example: true,
+    };
+    let content = generate::generate(day, &options)?;
+    // `test` in the name select the example parameters of day15
+    let name = format!("day{:02}_test_determinism", day);
+    let input = Input::from_bytes(&name, content.clone().into_bytes());
+    let equivalents = (1..config.runs)
+      .map(|run| match run % 2 {
+        0 => equivalent(day, &mut rng, &content),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    for (variant, solver) in &variants {
+      let expected = run_seeded(*solver, &input, 0);
+      for equivalent in &equivalents {
+        let hash_seed = rng.next_u64();
+        let actual = match equivalent {
+          Some(equivalent) => {
+            let input = Input::from_bytes(&name, equivalent.clone().into_bytes());
+            run_seeded(*solver, &input, hash_seed)
+          }
+          None => run_seeded(*solver, &input, hash_seed),
+        };
+        if !same(&expected, &actual) {
+          findings.push(Finding {
+            day,
+            variant,
+            seed,
+            size,
+            hash_seed,
+            equivalent: equivalent.clone(),
+            expected,
+            actual,
+          });
+          break;
+        }
+      }
+    }
+  }
+  Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn equivalent_inputs() {
+    let mut rng = Rng::new(3);
+    let content = "1,2,3\n4,5,6\n";
+    let equivalent = equivalent(18, &mut rng, content).unwrap();
+    let mut cubes = equivalent
+      .lines()
+      .map(|line| {
+        let mut coordinates = line.split(',').map(|c| c.parse::<u32>().unwrap()).collect::<Vec<_>>();
+        coordinates.sort();
+        coordinates
+      })
+      .collect::<Vec<_>>();
+    cubes.sort();
+    assert_eq!(cubes, vec![vec![1, 2, 3], vec![4, 5, 6]]);
+    assert!(equivalent.ends_with('\n'));
+
+    let valve = "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB\n";
+    let shuffled = super::equivalent(16, &mut rng, valve).unwrap();
+    let (head, tunnels) = shuffled.trim_end().split_once("valves ").unwrap();
+    assert_eq!(head, "Valve AA has flow rate=0; tunnels lead to ");
+    let mut tunnels = tunnels.split(", ").collect::<Vec<_>>();
+    tunnels.sort();
+    assert_eq!(tunnels, ["BB", "DD", "II"]);
+    assert_eq!(super::equivalent(5, &mut rng, content), None);
+  }
+
+  #[test]
+  fn answers_are_deterministic() -> Result<()> {
+    let config = Config {
+      inputs: 2,
+      runs: 4,
+      seed: 0,
+      size: None,
+    };
+    for day in generate::DAYS {
+      let config = Config {
+        size: generate::default_size(day).map(|size| size.min(40)),
+        ..config.clone()
+      };
+      if let Some(finding) = check_day(day, &config)?.first() {
+        panic!("{}", finding);
+      }
+    }
+    Ok(())
+  }
+}
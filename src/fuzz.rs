@@ -0,0 +1,183 @@
+//! Malformed inputs: solvers run on inputs pasted by users and must return an error instead of panicking.
+//! `run` is the entry point of the cargo-fuzz targets in `fuzz/`, `mutate` gives the same kind of inputs
+//! without libFuzzer by breaking generated inputs.
+use crate::generate;
+use crate::input::Input;
+use crate::property::{self, Outcome};
+use crate::registry;
+use crate::rng::Rng;
+use crate::Result;
+
+/// Names given to each fuzzed input, day15 uses the example settings when `test` is in the name
+fn input_names(day: u32) -> [String; 2] {
+  [format!("day{:02}_test_fuzz", day), format!("day{:02}_fuzz", day)]
+}
+
+/// Run every variant of `day` on `data`, a panic is a bug
+pub fn run(day: u32, data: &[u8]) {
+  for name in input_names(day) {
+    let input = Input::from_bytes(name, data.to_vec());
+    for (_, solver) in registry::variants(day) {
+      let _ = solver(&input);
+    }
+  }
+}
+
+/// Inputs that crashed a solver once, with their day
+pub const REGRESSIONS: &[(u32, &[u8])] = &[
+  // a huge head move, the visited positions grew for billions of steps
+  (9, b"R 4294967295\n"),
+  // a huge sensor radius, part1 allocated a position per covered column
+  (15, b"Sensor at x=0, y=0: closest beacon is at x=268000000, y=0\n"),
+  // sensors with a radius as large as the real search area, part2 collected millions of frontier points per sensor
+  (
+    15,
+    b"Sensor at x=2000000, y=2000000: closest beacon is at x=2000000, y=5900000
+Sensor at x=2000001, y=2000000: closest beacon is at x=2000001, y=5900000
+Sensor at x=2000002, y=2000000: closest beacon is at x=2000002, y=5900000
+",
+  ),
+];
+
+/// Bytes that matter to the parsers, picked more often than random ones
+const INTERESTING: &[&[u8]] = &[
+  b"\n",
+  b"\n\n",
+  b" ",
+  b"-",
+  b",",
+  b"0",
+  b"1",
+  b"9",
+  b"a",
+  b"z",
+  b"S",
+  b"E",
+  b"old",
+  b"99999999999999999999",
+  b"4294967296",
+  b"-2147483648",
+  b"\xff",
+];
+
+/// Break `data` with a few random edits
+pub fn mutate(rng: &mut Rng, data: &mut Vec<u8>) {
+  for _ in 0..=rng.below(4) {
+    let position = if data.is_empty() { 0 } else { rng.index(data.len() + 1) };
+    match rng.below(6) {
+      // overwrite a byte
+      0 if position < data.len() => data[position] = rng.below(256) as u8,
+      // insert a token
+      1 => {
+        let token = rng.choose(INTERESTING);
+        data.splice(position..position, token.iter().copied());
+      }
+      // remove a range
+      2 if position < data.len() => {
+        let end = (position + 1 + rng.index(16)).min(data.len());
+        data.drain(position..end);
+      }
+      // duplicate a range
+      3 if position < data.len() => {
+        let end = (position + 1 + rng.index(64)).min(data.len());
+        let copy = data[position..end].to_vec();
+        data.splice(end..end, copy);
+      }
+      // truncate
+      4 => data.truncate(position),
+      _ => {
+        let token = rng.choose(INTERESTING);
+        data.splice(position..position, token.iter().copied());
+      }
+    }
+  }
+}
+
+/// Input on which a solver panicked
+#[derive(Debug, Clone)]
+pub struct Crash {
+  pub solver: &'static str,
+  pub message: String,
+  /// Name of the input, day15 uses the example settings when `test` is in it
+  pub input_name: String,
+  pub input: Vec<u8>,
+}
+
+impl std::fmt::Display for Crash {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(
+      f,
+      "{} panicked: {}\ninput {}: {:?}",
+      self.solver,
+      self.message,
+      self.input_name,
+      String::from_utf8_lossy(&self.input)
+    )
+  }
+}
+
+/// Run `cases` broken generated inputs (and a few empty or random ones) through every variant of `day`
+pub fn fuzz_day(day: u32, cases: u32, seed: u64) -> Result<Option<Crash>> {
+  let variants = registry::variants(day);
+  let mut rng = Rng::new(seed ^ day as u64);
+  for case in 0..cases {
+    let mut data = match case {
+      0 => Vec::new(),
+      1 => (0..rng.index(64)).map(|_| rng.below(256) as u8).collect(),
+      _ => {
+        let options = generate::Options {
+          seed: rng.next_u64(),
+          size: Some(1 + rng.index(generate::default_size(day).unwrap_or(1).min(20))),
+          example: true,
+        };
+        generate::generate(day, &options)?.into_bytes()
+      }
+    };
+    if case > 1 {
+      mutate(&mut rng, &mut data);
+    }
+    for input_name in input_names(day) {
+      let input = Input::from_bytes(&input_name, data.clone());
+      for (name, solver) in &variants {
+        if let Outcome::Panic(message) = property::run(*solver, &input) {
+          return Ok(Some(Crash {
+            solver: name,
+            message,
+            input_name,
+            input: data,
+          }));
+        }
+      }
+    }
+  }
+  Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn regressions_dont_panic() {
+    for (day, data) in REGRESSIONS {
+      for input_name in input_names(*day) {
+        let input = Input::from_bytes(input_name, data.to_vec());
+        for (name, solver) in registry::variants(*day) {
+          if let Outcome::Panic(message) = property::run(solver, &input) {
+            panic!("{} panicked: {}", name, message);
+          }
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn malformed_inputs_dont_panic() -> Result<()> {
+    for day in generate::DAYS {
+      if let Some(crash) = fuzz_day(day, 30, 0)? {
+        panic!("{}", crash);
+      }
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,832 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::days;
+use crate::rng::Rng;
+use crate::Result;
+
+/// Days with an input generator
+pub const DAYS: [u32; 17] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 14, 15, 16, 17, 18];
+
+/// Generator settings
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+  pub seed: u64,
+  /// Size of the input, its meaning depends on the day (lines, elves, monkeys, grid width, ...)
+  /// Default to the size of a real input.
+  pub size: Option<usize>,
+  /// Use the example parameters when they differ from the real input (day15 search area),
+  /// solvers only use them when the input path contains `test`.
+  pub example: bool,
+}
+
+/// Generate a valid input for `day`, the same options always give the same input
+/// # Example
+/// ```
+/// use aoc_2022::generate::{generate, Options};
+///
+/// let options = Options { seed: 7, size: Some(10), ..Options::default() };
+/// let input = generate(1, &options).unwrap();
+/// assert_eq!(input.split("\n\n").count(), 10);
+/// assert_eq!(input, generate(1, &options).unwrap());
+/// ```
+pub fn generate(day: u32, options: &Options) -> Result<String> {
+  let mut rng = Rng::new(options.seed);
+  let size = options
+    .size
+    .or_else(|| default_size(day))
+    .ok_or(format!("No generator for day {}", day))?;
+  match day {
+    1 => day01(&mut rng, size),
+    2 => day02(&mut rng, size),
+    3 => day03(&mut rng, size),
+    4 => day04(&mut rng, size),
+    5 => day05(&mut rng, size),
+    6 => day06(&mut rng, size),
+    7 => day07(&mut rng, size),
+    8 => day08(&mut rng, size),
+    9 => day09(&mut rng, size),
+    10 => day10(&mut rng, size),
+    11 => day11(&mut rng, size),
+    12 => day12(&mut rng, size),
+    14 => day14(&mut rng, size),
+    15 => day15(&mut rng, size, options.example),
+    16 => day16(&mut rng, size),
+    17 => day17(&mut rng, size),
+    18 => day18(&mut rng, size),
+    _ => Err(format!("No generator for day {}", day).into()),
+  }
+}
+
+/// Size of a real input for `day`
+pub fn default_size(day: u32) -> Option<usize> {
+  match day {
+    1 => Some(250),
+    2 => Some(2500),
+    3 => Some(300),
+    4 => Some(1000),
+    5 => Some(500),
+    6 => Some(4096),
+    7 => Some(300),
+    8 => Some(99),
+    9 => Some(2000),
+    10 => Some(240),
+    11 => Some(8),
+    12 => Some(160),
+    14 => Some(150),
+    15 => Some(30),
+    16 => Some(60),
+    17 => Some(10091),
+    18 => Some(2800),
+    _ => None,
+  }
+}
+
+/// Calories of each elf, one blank line between elves
+fn day01(rng: &mut Rng, elves: usize) -> Result<String> {
+  let mut out = String::new();
+  for elf in 0..elves.max(3) {
+    if elf != 0 {
+      out.push('\n');
+    }
+    for _ in 0..rng.range(1, 16) {
+      writeln!(out, "{}", rng.range(1000, 20000))?;
+    }
+  }
+  Ok(out)
+}
+
+fn day02(rng: &mut Rng, rounds: usize) -> Result<String> {
+  let mut out = String::new();
+  for _ in 0..rounds {
+    writeln!(out, "{} {}", rng.choose(&['A', 'B', 'C']), rng.choose(&['X', 'Y', 'Z']))?;
+  }
+  Ok(out)
+}
+
+const ITEMS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Groups of three rucksacks, one item type is in both compartments of a rucksack
+/// and only the badge is in the three rucksacks of a group
+fn day03(rng: &mut Rng, rucksacks: usize) -> Result<String> {
+  let mut out = String::new();
+  for _ in 0..(rucksacks / 3).max(1) {
+    let badge = *rng.choose(ITEMS);
+    // Other item types are each missing from one rucksack of the group
+    let mut allowed = [Vec::new(), Vec::new(), Vec::new()];
+    for &item in ITEMS.iter().filter(|&&item| item != badge) {
+      let missing = rng.index(3);
+      for (_, types) in allowed.iter_mut().enumerate().filter(|(index, _)| *index != missing) {
+        types.push(item);
+      }
+    }
+    for types in &mut allowed {
+      rng.shuffle(types);
+      // Compartments don't share types except the first one of the left side
+      let (left_types, right_types) = types.split_at(types.len() / 2);
+      let shared = left_types[0];
+      let half = rng.range(4, 17) as usize;
+      let mut left = vec![shared];
+      let mut right = vec![shared];
+      if rng.chance(1, 2) {
+        left.push(badge);
+      } else {
+        right.push(badge);
+      }
+      while left.len() < half {
+        left.push(*rng.choose(left_types));
+      }
+      while right.len() < half {
+        right.push(*rng.choose(right_types));
+      }
+      rng.shuffle(&mut left);
+      rng.shuffle(&mut right);
+      writeln!(out, "{}{}", String::from_utf8(left)?, String::from_utf8(right)?)?;
+    }
+  }
+  Ok(out)
+}
+
+fn day04(rng: &mut Rng, pairs: usize) -> Result<String> {
+  let mut out = String::new();
+  for _ in 0..pairs {
+    let first = rng.range(1, 100);
+    let last = rng.range(first, 100);
+    let other_first = rng.range(1, 100);
+    let other_last = rng.range(other_first, 100);
+    writeln!(out, "{}-{},{}-{}", first, last, other_first, other_last)?;
+  }
+  Ok(out)
+}
+
+/// 9 to 12 stacks and moves that never empty a stack, so there is always a crate on top at the end
+fn day05(rng: &mut Rng, moves: usize) -> Result<String> {
+  // real inputs have 9 stacks, hand-edited ones more
+  let mut stacks = (0..rng.range(9, 13))
+    .map(|_| {
+      (0..rng.range(1, 9))
+        .map(|_| *rng.choose(&ITEMS[26..]))
+        .collect::<Vec<_>>()
+    })
+    .collect::<Vec<_>>();
+  // At least one stack must be able to give a crate
+  if stacks.iter().all(|stack| stack.len() < 2) {
+    stacks[0].push(b'A');
+  }
+
+  let mut out = String::new();
+  let height = stacks.iter().map(|stack| stack.len()).max().unwrap_or(0);
+  for level in (0..height).rev() {
+    let line = stacks
+      .iter()
+      .map(|stack| {
+        stack
+          .get(level)
+          .map_or("   ".to_string(), |&item| format!("[{}]", item as char))
+      })
+      .join(" ");
+    writeln!(out, "{}", line)?;
+  }
+  writeln!(
+    out,
+    "{}",
+    (1..=stacks.len())
+      .map(|index| format!("{:^3}", index))
+      .join(" ")
+      .trim_end()
+  )?;
+  writeln!(out)?;
+
+  let mut heights = stacks.iter().map(|stack| stack.len()).collect::<Vec<_>>();
+  for _ in 0..moves {
+    let sources = (0..heights.len())
+      .filter(|&index| heights[index] >= 2)
+      .collect::<Vec<_>>();
+    let src = *rng.choose(&sources);
+    let dst = (src + 1 + rng.index(heights.len() - 1)) % heights.len();
+    let quantity = rng.range(1, heights[src] as i64) as usize;
+    heights[src] -= quantity;
+    heights[dst] += quantity;
+    writeln!(out, "move {} from {} to {}", quantity, src + 1, dst + 1)?;
+  }
+  Ok(out)
+}
+
+/// Datastream mostly made of recent letters, with a start-of-message marker in the second half
+fn day06(rng: &mut Rng, length: usize) -> Result<String> {
+  let length = length.max(32);
+  let mut stream = Vec::with_capacity(length + 1);
+  while stream.len() < length {
+    let byte = if !stream.is_empty() && rng.chance(3, 4) {
+      stream[stream.len() - 1 - rng.index(stream.len().min(4))]
+    } else {
+      *rng.choose(&ITEMS[..26])
+    };
+    stream.push(byte);
+  }
+  let mut letters = ITEMS[..26].to_vec();
+  rng.shuffle(&mut letters);
+  let position = rng.range(length as i64 / 2, length as i64 - 13) as usize;
+  stream[position..position + 14].copy_from_slice(&letters[..14]);
+  stream.push(b'\n');
+  Ok(String::from_utf8(stream)?)
+}
+
+/// Random file system explored depth first, the used space is between 40M and 70M
+/// so there is always something to delete in part2
+fn day07(rng: &mut Rng, files: usize) -> Result<String> {
+  let files = files.max(1);
+  let directories = (files / 3).max(1);
+  let mut children = vec![Vec::new(); directories];
+  let mut names = vec![String::from("/")];
+  for directory in 1..directories {
+    let parent = rng.index(directory);
+    let name = loop {
+      let name = word(rng);
+      if children[parent].iter().all(|&child: &usize| names[child] != name) {
+        break name;
+      }
+    };
+    children[parent].push(directory);
+    names.push(name);
+  }
+
+  let weights = (0..files).map(|_| rng.range(1, 300_000) as u64).collect::<Vec<_>>();
+  let total = rng.range(45_000_000, 65_000_000) as u64;
+  let weight_sum = weights.iter().sum::<u64>();
+  let mut content = vec![Vec::new(); directories];
+  for weight in weights {
+    let size = (weight as u128 * total as u128 / weight_sum as u128).max(1);
+    content[rng.index(directories)].push(format!("{} {}.{}", size, word(rng), word(rng)));
+  }
+
+  fn explore(
+    directory: usize,
+    children: &[Vec<usize>],
+    names: &[String],
+    content: &mut [Vec<String>],
+    rng: &mut Rng,
+    out: &mut String,
+  ) -> Result<()> {
+    writeln!(out, "$ ls")?;
+    let mut entries = children[directory]
+      .iter()
+      .map(|&child| format!("dir {}", names[child]))
+      .chain(content[directory].drain(..))
+      .collect::<Vec<_>>();
+    rng.shuffle(&mut entries);
+    for entry in entries {
+      writeln!(out, "{}", entry)?;
+    }
+    for &child in &children[directory] {
+      writeln!(out, "$ cd {}", names[child])?;
+      explore(child, children, names, content, rng, out)?;
+      writeln!(out, "$ cd ..")?;
+    }
+    Ok(())
+  }
+
+  let mut out = String::from("$ cd /\n");
+  explore(0, &children, &names, &mut content, rng, &mut out)?;
+  Ok(out)
+}
+
+fn word(rng: &mut Rng) -> String {
+  (0..rng.range(1, 8))
+    .map(|_| *rng.choose(&ITEMS[..26]) as char)
+    .collect()
+}
+
+fn day08(rng: &mut Rng, side: usize) -> Result<String> {
+  let mut out = String::new();
+  for _ in 0..side.max(1) {
+    let line = (0..side.max(1))
+      .map(|_| (b'0' + rng.below(10) as u8) as char)
+      .collect::<String>();
+    writeln!(out, "{}", line)?;
+  }
+  Ok(out)
+}
+
+fn day09(rng: &mut Rng, moves: usize) -> Result<String> {
+  let mut out = String::new();
+  for _ in 0..moves {
+    writeln!(out, "{} {}", rng.choose(&['U', 'D', 'L', 'R']), rng.range(1, 20))?;
+  }
+  Ok(out)
+}
+
+/// Program running at least 240 cycles, the register mostly stays on the screen
+fn day10(rng: &mut Rng, cycles: usize) -> Result<String> {
+  let cycles = cycles.max(240);
+  let mut out = String::new();
+  let mut register = 1;
+  let mut cycle = 0;
+  while cycle < cycles {
+    if cycles - cycle >= 2 && rng.chance(2, 3) {
+      let increment = (register + rng.range(-15, 16)).clamp(-1, 40) - register;
+      writeln!(out, "addx {}", increment)?;
+      register += increment;
+      cycle += 2;
+    } else {
+      writeln!(out, "noop")?;
+      cycle += 1;
+    }
+  }
+  Ok(out)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+  Add(u64),
+  Mul(u64),
+  Square,
+}
+
+struct Monkey {
+  items: Vec<u64>,
+  operation: Operation,
+  divisor: u64,
+  if_true: usize,
+  if_false: usize,
+}
+
+/// Monkeys like the real input: distinct prime divisors, one `old * old`, one or two products and additions.
+/// The number of monkeys is kept under 10 as the header only has one digit, and the product of divisors
+/// squared must fit in u64 for part2.
+fn day11(rng: &mut Rng, monkeys: usize) -> Result<String> {
+  const PRIMES: [u64; 9] = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+  let count = monkeys.clamp(3, PRIMES.len());
+  for _ in 0..1000 {
+    let mut divisors = PRIMES.to_vec();
+    rng.shuffle(&mut divisors);
+    let mut operations = (0..count)
+      .map(|index| match index {
+        0 => Operation::Square,
+        1 => Operation::Mul(rng.range(2, 20) as u64),
+        2 if rng.chance(1, 2) => Operation::Mul(rng.range(2, 20) as u64),
+        _ => Operation::Add(rng.range(1, 9) as u64),
+      })
+      .collect::<Vec<_>>();
+    rng.shuffle(&mut operations);
+    let monkeys = (0..count)
+      .map(|index| {
+        let if_true = (index + 1 + rng.index(count - 1)) % count;
+        let if_false = loop {
+          let target = rng.index(count);
+          if target != index && target != if_true {
+            break target;
+          }
+        };
+        Monkey {
+          items: (0..rng.range(1, 9)).map(|_| rng.range(50, 100) as u64).collect(),
+          operation: operations[index],
+          divisor: divisors[index],
+          if_true,
+          if_false,
+        }
+      })
+      .collect::<Vec<_>>();
+
+    if !part1_fits(&monkeys) {
+      continue;
+    }
+    let mut out = String::new();
+    for (index, monkey) in monkeys.iter().enumerate() {
+      if index != 0 {
+        writeln!(out)?;
+      }
+      writeln!(out, "Monkey {}:", index)?;
+      writeln!(out, "  Starting items: {}", monkey.items.iter().join(", "))?;
+      match monkey.operation {
+        Operation::Add(value) => writeln!(out, "  Operation: new = old + {}", value)?,
+        Operation::Mul(value) => writeln!(out, "  Operation: new = old * {}", value)?,
+        Operation::Square => writeln!(out, "  Operation: new = old * old")?,
+      }
+      writeln!(out, "  Test: divisible by {}", monkey.divisor)?;
+      writeln!(out, "    If true: throw to monkey {}", monkey.if_true)?;
+      writeln!(out, "    If false: throw to monkey {}", monkey.if_false)?;
+    }
+    return Ok(out);
+  }
+  Err("Fail to generate monkeys without overflow".into())
+}
+
+/// Worry levels of part1 are not reduced by a modulo, check they don't overflow
+fn part1_fits(monkeys: &[Monkey]) -> bool {
+  let mut items = monkeys.iter().map(|monkey| monkey.items.clone()).collect::<Vec<_>>();
+  for _round in 0..20 {
+    for (index, monkey) in monkeys.iter().enumerate() {
+      for item in std::mem::take(&mut items[index]) {
+        let worry_level = match monkey.operation {
+          Operation::Add(value) => item.checked_add(value),
+          Operation::Mul(value) => item.checked_mul(value),
+          Operation::Square => item.checked_mul(item),
+        };
+        let Some(worry_level) = worry_level else {
+          return false;
+        };
+        let after_bored = worry_level / 3;
+        let target = if after_bored % monkey.divisor == 0 {
+          monkey.if_true
+        } else {
+          monkey.if_false
+        };
+        items[target].push(after_bored);
+      }
+    }
+  }
+  true
+}
+
+/// Heightmap with a path from `S` to `E` climbing one step at a time
+fn day12(rng: &mut Rng, width: usize) -> Result<String> {
+  let width = width.max(16);
+  let height = (width / 4).max(8);
+  // Low ground around so the climb has to follow the path
+  let mut grid = (0..width * height).map(|_| *rng.choose(b"aaabbc")).collect::<Vec<_>>();
+
+  // Self avoiding random walk, it's long enough to go from a to z most of the time
+  let mut path = Vec::new();
+  for _ in 0..1000 {
+    let start = (0usize, rng.index(height));
+    path = vec![start];
+    let mut visited = HashSet::from([start]);
+    while path.len() < width * height / 4 {
+      let (x, y) = path[path.len() - 1];
+      let next = [(x + 1, y), (x.wrapping_sub(1), y), (x, y + 1), (x, y.wrapping_sub(1))]
+        .into_iter()
+        .filter(|&(x, y)| x < width && y < height && !visited.contains(&(x, y)))
+        .collect::<Vec<_>>();
+      if next.is_empty() {
+        break;
+      }
+      let next = *rng.choose(&next);
+      visited.insert(next);
+      path.push(next);
+    }
+    if path.len() >= 27 {
+      break;
+    }
+  }
+  if path.len() < 27 {
+    // Fallback on a path going back and forth on the lines
+    path = (0..height)
+      .flat_map(|y| (0..width).map(move |x| if y % 2 == 0 { (x, y) } else { (width - 1 - x, y) }))
+      .collect();
+  }
+
+  let last = path.len() - 1;
+  for (index, &(x, y)) in path.iter().enumerate() {
+    grid[x + y * width] = match index {
+      0 => b'S',
+      index if index == last => b'E',
+      index => b'a' + (index * 25 / (last - 1)).min(25) as u8,
+    };
+  }
+  let mut out = String::new();
+  for line in grid.chunks(width) {
+    writeln!(out, "{}", std::str::from_utf8(line)?)?;
+  }
+  Ok(out)
+}
+
+/// Rock paths under the sand source, they stay in the area where the sand can go in part2
+fn day14(rng: &mut Rng, paths: usize) -> Result<String> {
+  let depth = paths.max(1) as i64 + 20;
+  let mut out = String::new();
+  // Deepest path is right under the source so the sand never goes out of the scan in x
+  let left = rng.range(2, 10);
+  writeln!(out, "{},{} -> {},{}", 500 - left, depth, 500 + rng.range(1, 10), depth)?;
+  for _ in 1..paths {
+    let mut point = (500 + rng.range(-depth / 2, depth / 2 + 1), rng.range(2, depth + 1));
+    let mut points = vec![point];
+    let mut horizontal = rng.chance(1, 2);
+    for _ in 0..rng.range(1, 6) {
+      let length = rng.range(1, 9) * if rng.chance(1, 2) { 1 } else { -1 };
+      let next = if horizontal {
+        ((point.0 + length).clamp(500 - depth + 2, 500 + depth - 2), point.1)
+      } else {
+        (point.0, (point.1 + length).clamp(2, depth))
+      };
+      horizontal = !horizontal;
+      // Empty segments are not valid
+      if next != point {
+        points.push(next);
+        point = next;
+      }
+    }
+    writeln!(
+      out,
+      "{}",
+      points.iter().map(|(x, y)| format!("{},{}", x, y)).join(" -> ")
+    )?;
+  }
+  Ok(out)
+}
+
+#[inline]
+fn manhattan(a: (i64, i64), b: (i64, i64)) -> i64 {
+  (a.0 - b.0).abs() + (a.1 - b.1).abs()
+}
+
+struct Sensor {
+  position: (i64, i64),
+  beacon: (i64, i64),
+  radius: i64,
+}
+
+/// Sensors covering the whole search area except the distress beacon.
+/// The input stays consistent: a sensor closest beacon is unique and no beacon is strictly in range of another sensor.
+fn day15(rng: &mut Rng, sensors_count: usize, example: bool) -> Result<String> {
+  let side: i64 = if example { 20 } else { 4_000_000 };
+  let distress = (rng.range(0, side + 1), rng.range(0, side + 1));
+  let mut sensors = Vec::new();
+  // Four sensors around the distress beacon, their borders pass right next to it like in real inputs
+  // Their beacons are on the far side so they don't limit each other range.
+  for (x, y) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+    let sensor = (0..100)
+      .map(|_| {
+        let position = (
+          distress.0 + x * rng.range(1, side / 8 + 2),
+          distress.1 + y * rng.range(1, side / 8 + 2),
+        );
+        let radius = manhattan(position, distress) - 1;
+        let dx = rng.range(0, radius + 1);
+        let beacon = (position.0 + x * dx, position.1 + y * (radius - dx));
+        Sensor {
+          position,
+          beacon,
+          radius,
+        }
+      })
+      .find(|new| {
+        sensors.iter().all(|sensor: &Sensor| {
+          manhattan(sensor.position, new.beacon) > sensor.radius && manhattan(new.position, sensor.beacon) > new.radius
+        })
+      })
+      .ok_or("Fail to place sensors around the distress beacon")?;
+    sensors.push(sensor);
+  }
+
+  let typical_radius = ((2 * side) as f64 / (sensors_count.max(1) as f64).sqrt()) as i64 + 1;
+  while let Some(target) = uncovered_point(&sensors, side, distress) {
+    if sensors.len() > 100 * sensors_count.max(1) + 1000 {
+      return Err("Fail to cover the search area".into());
+    }
+    // New sensor around the uncovered point, sometimes right on it so the point is always covered in the end
+    let spread = if rng.chance(1, 4) { 0 } else { typical_radius / 2 };
+    let position = (
+      target.0 + rng.range(-spread, spread + 1),
+      target.1 + rng.range(-spread, spread + 1),
+    );
+    let radius = rng
+      .range(typical_radius / 2, typical_radius + 1)
+      .max(manhattan(position, target));
+    add_sensor(rng, &mut sensors, position, radius, distress);
+  }
+
+  let mut out = String::new();
+  for sensor in sensors {
+    writeln!(
+      out,
+      "Sensor at x={}, y={}: closest beacon is at x={}, y={}",
+      sensor.position.0, sensor.position.1, sensor.beacon.0, sensor.beacon.1
+    )?;
+  }
+  Ok(out)
+}
+
+/// Add a sensor at `position` with the largest consistent radius up to `max_radius`
+fn add_sensor(rng: &mut Rng, sensors: &mut Vec<Sensor>, position: (i64, i64), max_radius: i64, distress: (i64, i64)) {
+  let mut radius = max_radius.min(manhattan(position, distress) - 1);
+  if radius < 0 || sensors.iter().any(|sensor| sensor.position == position) {
+    return;
+  }
+  // Reuse the closest existing beacon when it's in range and there is no tie
+  if let Some((distance, beacon)) = sensors
+    .iter()
+    .map(|sensor| (manhattan(position, sensor.beacon), sensor.beacon))
+    .min()
+  {
+    if distance <= radius {
+      let tie = sensors
+        .iter()
+        .any(|sensor| sensor.beacon != beacon && manhattan(position, sensor.beacon) == distance);
+      if !tie {
+        sensors.push(Sensor {
+          position,
+          beacon,
+          radius: distance,
+        });
+        return;
+      }
+      radius = distance - 1;
+    }
+  }
+  // Or put a new beacon on the border, out of range of the other sensors
+  for _ in 0..64 {
+    let dx = rng.range(-radius, radius + 1);
+    let dy = (radius - dx.abs()) * if rng.chance(1, 2) { 1 } else { -1 };
+    let beacon = (position.0 + dx, position.1 + dy);
+    if beacon != distress
+      && sensors
+        .iter()
+        .all(|sensor| manhattan(sensor.position, beacon) > sensor.radius)
+    {
+      sensors.push(Sensor {
+        position,
+        beacon,
+        radius,
+      });
+      return;
+    }
+  }
+}
+
+/// A point of the search area out of range of every sensor, other than the distress beacon.
+/// Uncovered areas are bounded by lines just outside sensor ranges, so their corners are checked first.
+/// A full scan of the area ends the search.
+fn uncovered_point(sensors: &[Sensor], side: i64, distress: (i64, i64)) -> Option<(i64, i64)> {
+  let uncovered = |point: (i64, i64)| {
+    (0..=side).contains(&point.0)
+      && (0..=side).contains(&point.1)
+      && point != distress
+      && sensors
+        .iter()
+        .all(|sensor| manhattan(sensor.position, point) > sensor.radius)
+  };
+  // x + y = sum and x - y = diff just outside each sensor range
+  let mut sums = Vec::new();
+  let mut diffs = Vec::new();
+  for sensor in sensors {
+    let (x, y) = sensor.position;
+    for offset in [-sensor.radius - 1, sensor.radius + 1] {
+      sums.push(x + y + offset);
+      diffs.push(x - y + offset);
+    }
+  }
+  let corners = [(0, 0), (0, side), (side, 0), (side, side)];
+  let on_borders = sums
+    .iter()
+    .flat_map(|&sum| [(0, sum), (side, sum - side), (sum, 0), (sum - side, side)])
+    .chain(
+      diffs
+        .iter()
+        .flat_map(|&diff| [(0, -diff), (side, side - diff), (diff, 0), (side + diff, side)]),
+    );
+  let crossings = sums.iter().flat_map(|&sum| {
+    diffs.iter().flat_map(move |&diff| {
+      let x = (sum + diff).div_euclid(2);
+      [(x, sum - x), (x, x - diff), (x + 1, sum - x - 1), (x + 1, x + 1 - diff)]
+    })
+  });
+  if let Some(point) = corners
+    .into_iter()
+    .chain(on_borders)
+    .chain(crossings)
+    .find(|&point| uncovered(point))
+  {
+    return Some(point);
+  }
+
+  // Scan line by line with ranges
+  let mut ranges = Vec::with_capacity(sensors.len());
+  for y in 0..=side {
+    ranges.clear();
+    for sensor in sensors {
+      let width = sensor.radius - (sensor.position.1 - y).abs();
+      if width >= 0 {
+        ranges.push((sensor.position.0 - width, sensor.position.0 + width));
+      }
+    }
+    ranges.sort_unstable();
+    let mut x = 0;
+    for &(start, end) in &ranges {
+      while x < start && x <= side {
+        if (x, y) != distress {
+          return Some((x, y));
+        }
+        x += 1;
+      }
+      x = x.max(end + 1);
+    }
+    while x <= side {
+      if (x, y) != distress {
+        return Some((x, y));
+      }
+      x += 1;
+    }
+  }
+  None
+}
+
+/// Connected valve graph with up to 15 valves with a flow rate, within the valve limits of the solver
+fn day16(rng: &mut Rng, valves: usize) -> Result<String> {
+  let count = valves.clamp(2, days::day16::MAX_VALVES);
+  let mut names = vec![String::from("AA")];
+  let mut used = HashSet::from([String::from("AA")]);
+  while names.len() < count {
+    let name = (0..2).map(|_| *rng.choose(&ITEMS[26..]) as char).collect::<String>();
+    if used.insert(name.clone()) {
+      names.push(name);
+    }
+  }
+
+  let mut tunnels = vec![Vec::new(); count];
+  let mut connect = |a: usize, b: usize| {
+    if a != b && !tunnels[a].contains(&b) {
+      tunnels[a].push(b);
+      tunnels[b].push(a);
+    }
+  };
+  for valve in 1..count {
+    connect(valve, rng.index(valve));
+  }
+  for _ in 0..count / 4 {
+    connect(rng.index(count), rng.index(count));
+  }
+
+  let mut flows = vec![0; count];
+  let mut with_flow = (1..count).collect::<Vec<_>>();
+  rng.shuffle(&mut with_flow);
+  let flow_count = (count / 4).clamp(1, 15.min(days::day16::MAX_VALVES_WITH_FLOW));
+  for &valve in with_flow.iter().take(flow_count) {
+    flows[valve] = rng.range(2, 26);
+  }
+
+  let mut order = (0..count).collect::<Vec<_>>();
+  rng.shuffle(&mut order);
+  let mut out = String::new();
+  for valve in order {
+    let next = tunnels[valve].iter().map(|&next| &names[next]).join(", ");
+    if tunnels[valve].len() == 1 {
+      writeln!(
+        out,
+        "Valve {} has flow rate={}; tunnel leads to valve {}",
+        names[valve], flows[valve], next
+      )?;
+    } else {
+      writeln!(
+        out,
+        "Valve {} has flow rate={}; tunnels lead to valves {}",
+        names[valve], flows[valve], next
+      )?;
+    }
+  }
+  Ok(out)
+}
+
+fn day17(rng: &mut Rng, jets: usize) -> Result<String> {
+  let mut out = (0..jets.max(1)).map(|_| *rng.choose(&['<', '>'])).collect::<String>();
+  out.push('\n');
+  Ok(out)
+}
+
+/// Distinct cubes in a 20x20x20 area
+fn day18(rng: &mut Rng, cubes: usize) -> Result<String> {
+  let mut positions = (0..20 * 20 * 20).collect::<Vec<_>>();
+  rng.shuffle(&mut positions);
+  let mut out = String::new();
+  for position in positions.into_iter().take(cubes.max(1)) {
+    writeln!(out, "{},{},{}", position % 20, position / 20 % 20, position / 400)?;
+  }
+  Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn generate_all_days() -> Result<()> {
+    for day in DAYS {
+      let options = Options {
+        seed: 3,
+        size: Some(20),
+        example: true,
+      };
+      let input = generate(day, &options)?;
+      assert!(input.ends_with('\n'), "day{:02} input must end with a new line", day);
+      assert_eq!(input, generate(day, &options)?);
+    }
+    assert!(generate(13, &Options::default()).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn day16_within_solver_limits() -> Result<()> {
+    let options = Options {
+      seed: 3,
+      size: Some(1000),
+      example: false,
+    };
+    let input = generate(16, &options)?;
+    let with_flow = input.lines().filter(|line| !line.contains("rate=0;")).count();
+    assert_eq!(input.lines().count(), days::day16::MAX_VALVES);
+    assert!(with_flow <= days::day16::MAX_VALVES_WITH_FLOW);
+    Ok(())
+  }
+}
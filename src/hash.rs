@@ -0,0 +1,90 @@
+use std::cell::Cell;
+use std::hash::{BuildHasherDefault, Hasher};
+
+/// Multiply-rotate hasher (same scheme as rustc's FxHash)
+/// Far faster than SipHash on small integer keys but not DoS resistant, fine for puzzle inputs
+#[derive(Debug, Clone, Copy)]
+pub struct FastHasher {
+  hash: u64,
+}
+
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+thread_local! {
+  static INITIAL_HASH: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Start the hashers created by this thread from `seed`, 0 by default.
+/// Only changes the iteration order of the maps, answers must not depend on it.
+pub fn set_seed(seed: u64) {
+  INITIAL_HASH.with(|hash| hash.set(seed));
+}
+
+impl Default for FastHasher {
+  #[inline(always)]
+  fn default() -> Self {
+    Self {
+      hash: INITIAL_HASH.with(Cell::get),
+    }
+  }
+}
+
+impl FastHasher {
+  #[inline(always)]
+  fn add_to_hash(&mut self, word: u64) {
+    self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(SEED);
+  }
+}
+
+impl Hasher for FastHasher {
+  #[inline]
+  fn write(&mut self, bytes: &[u8]) {
+    let mut chunks = bytes.chunks_exact(8);
+    for chunk in &mut chunks {
+      let mut word = [0; 8];
+      word.copy_from_slice(chunk);
+      self.add_to_hash(u64::from_le_bytes(word));
+    }
+    for &byte in chunks.remainder() {
+      self.add_to_hash(byte as u64);
+    }
+  }
+
+  #[inline(always)]
+  fn write_u8(&mut self, i: u8) {
+    self.add_to_hash(i as u64);
+  }
+
+  #[inline(always)]
+  fn write_u16(&mut self, i: u16) {
+    self.add_to_hash(i as u64);
+  }
+
+  #[inline(always)]
+  fn write_u32(&mut self, i: u32) {
+    self.add_to_hash(i as u64);
+  }
+
+  #[inline(always)]
+  fn write_u64(&mut self, i: u64) {
+    self.add_to_hash(i);
+  }
+
+  #[inline(always)]
+  fn write_usize(&mut self, i: usize) {
+    self.add_to_hash(i as u64);
+  }
+
+  #[inline(always)]
+  fn finish(&self) -> u64 {
+    self.hash
+  }
+}
+
+pub type FastBuildHasher = BuildHasherDefault<FastHasher>;
+
+/// HashMap using [`FastHasher`]
+pub type FastHashMap<K, V> = std::collections::HashMap<K, V, FastBuildHasher>;
+
+/// HashSet using [`FastHasher`]
+pub type FastHashSet<K> = std::collections::HashSet<K, FastBuildHasher>;
@@ -0,0 +1,239 @@
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use crate::Result;
+
+/// Puzzle input given to solvers
+/// Solvers can either get the whole content with [`Input::read_to_string`]
+/// or process it in constant memory with [`Input::reader`].
+/// An input loaded in memory can be shared between solvers so they all see the same buffer.
+#[derive(Debug)]
+pub struct Input {
+  path: PathBuf,
+  source: Source,
+}
+
+#[derive(Debug)]
+enum Source {
+  /// File is read on demand
+  File,
+  /// Content already loaded in memory
+  Memory(Vec<u8>),
+  /// File is mapped in memory
+  #[cfg(unix)]
+  Mmap(Mmap),
+}
+
+impl Input {
+  /// Input read from the file each time it's requested
+  pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+    Self {
+      path: path.as_ref().to_path_buf(),
+      source: Source::File,
+    }
+  }
+
+  /// Input loaded once in memory
+  pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+    let content = std::fs::read(path.as_ref())?;
+    Ok(Self::from_bytes(path, content))
+  }
+
+  /// Input from a buffer, `path` is only used as name
+  pub fn from_bytes<P: AsRef<Path>>(path: P, content: Vec<u8>) -> Self {
+    Self {
+      path: path.as_ref().to_path_buf(),
+      source: Source::Memory(content),
+    }
+  }
+
+  /// Input backed by a memory mapping of the file
+  #[cfg(unix)]
+  pub fn mmap<P: AsRef<Path>>(path: P) -> Result<Self> {
+    Ok(Self {
+      path: path.as_ref().to_path_buf(),
+      source: Source::Mmap(Mmap::open(path.as_ref())?),
+    })
+  }
+
+  /// Path of the input, solvers use it to switch between example and real input parameters
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  /// Size of the input in bytes
+  pub fn size(&self) -> Result<u64> {
+    match &self.source {
+      Source::File => Ok(std::fs::metadata(&self.path)?.len()),
+      Source::Memory(content) => Ok(content.len() as u64),
+      #[cfg(unix)]
+      Source::Mmap(map) => Ok(map.len() as u64),
+    }
+  }
+
+  /// Whole input content
+  pub fn read_to_string(&self) -> Result<Cow<'_, str>> {
+    match &self.source {
+      Source::File => Ok(Cow::Owned(std::fs::read_to_string(&self.path)?)),
+      Source::Memory(content) => Ok(Cow::Borrowed(std::str::from_utf8(content)?)),
+      #[cfg(unix)]
+      Source::Mmap(map) => Ok(Cow::Borrowed(std::str::from_utf8(map)?)),
+    }
+  }
+
+  /// Whole input content without utf-8 validation
+  pub fn read_bytes(&self) -> Result<Cow<'_, [u8]>> {
+    match &self.source {
+      Source::File => Ok(Cow::Owned(std::fs::read(&self.path)?)),
+      Source::Memory(content) => Ok(Cow::Borrowed(content)),
+      #[cfg(unix)]
+      Source::Mmap(map) => Ok(Cow::Borrowed(&map[..])),
+    }
+  }
+
+  /// Buffered reader on the input, memory usage doesn't depend on the input size
+  pub fn reader(&self) -> Result<Box<dyn BufRead + '_>> {
+    match &self.source {
+      Source::File => Ok(Box::new(BufReader::with_capacity(1 << 16, File::open(&self.path)?))),
+      Source::Memory(content) => Ok(Box::new(&content[..])),
+      #[cfg(unix)]
+      Source::Mmap(map) => Ok(Box::new(&map[..])),
+    }
+  }
+}
+
+/// Iterate over lines of a reader reusing the same buffer
+/// Line ending (`\n` or `\r\n`) is removed.
+/// # Example
+/// ```
+/// use aoc_2022::input::LineReader;
+///
+/// let mut lines = LineReader::new("a\nb\r\n".as_bytes());
+/// assert_eq!(lines.next_line().unwrap(), Some("a"));
+/// assert_eq!(lines.next_line().unwrap(), Some("b"));
+/// assert_eq!(lines.next_line().unwrap(), None);
+/// ```
+pub struct LineReader<R> {
+  reader: R,
+  buffer: String,
+}
+
+impl<R: BufRead> LineReader<R> {
+  pub fn new(reader: R) -> Self {
+    Self {
+      reader,
+      buffer: String::new(),
+    }
+  }
+
+  #[inline]
+  pub fn next_line(&mut self) -> Result<Option<&str>> {
+    self.buffer.clear();
+    if self.reader.read_line(&mut self.buffer)? == 0 {
+      return Ok(None);
+    }
+    let line = self.buffer.strip_suffix('\n').unwrap_or(&self.buffer);
+    Ok(Some(line.strip_suffix('\r').unwrap_or(line)))
+  }
+}
+
+/// Read-only memory mapping of a file
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct Mmap {
+  ptr: *mut libc::c_void,
+  len: usize,
+}
+
+#[cfg(unix)]
+impl Mmap {
+  pub fn open(path: &Path) -> Result<Self> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = File::open(path)?;
+    let len = usize::try_from(file.metadata()?.len())?;
+    if len == 0 {
+      // mmap doesn't accept empty mapping
+      return Ok(Self {
+        ptr: std::ptr::null_mut(),
+        len,
+      });
+    }
+    // SAFETY: we map a file we just opened, the mapping stay valid after the file is closed
+    let ptr = unsafe {
+      libc::mmap(
+        std::ptr::null_mut(),
+        len,
+        libc::PROT_READ,
+        libc::MAP_PRIVATE,
+        file.as_raw_fd(),
+        0,
+      )
+    };
+    if ptr == libc::MAP_FAILED {
+      return Err(std::io::Error::last_os_error().into());
+    }
+    // Only a hint, the mapping is usable even if it fails
+    #[cfg(target_os = "linux")]
+    unsafe {
+      libc::madvise(ptr, len, libc::MADV_SEQUENTIAL);
+    }
+    Ok(Self { ptr, len })
+  }
+}
+
+#[cfg(unix)]
+impl std::ops::Deref for Mmap {
+  type Target = [u8];
+
+  fn deref(&self) -> &[u8] {
+    if self.len == 0 {
+      return &[];
+    }
+    // SAFETY: the mapping is valid and readable until drop
+    unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+  }
+}
+
+#[cfg(unix)]
+impl Drop for Mmap {
+  fn drop(&mut self) {
+    if self.len != 0 {
+      unsafe {
+        libc::munmap(self.ptr, self.len);
+      }
+    }
+  }
+}
+
+// The mapping is read only and never aliased mutably
+#[cfg(unix)]
+unsafe impl Send for Mmap {}
+#[cfg(unix)]
+unsafe impl Sync for Mmap {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Read;
+
+  #[test]
+  #[cfg(unix)]
+  fn sources_give_same_content() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("aoc_2022_input_{}.txt", std::process::id()));
+    std::fs::write(&path, "1000\n2000\n\n3000\n")?;
+    let from_file = Input::from_path(&path);
+    for other in [Input::load(&path)?, Input::mmap(&path)?] {
+      assert_eq!(from_file.size()?, other.size()?);
+      assert_eq!(from_file.read_to_string()?, other.read_to_string()?);
+      assert_eq!(from_file.read_bytes()?, other.read_bytes()?);
+      let mut streamed = String::new();
+      other.reader()?.read_to_string(&mut streamed)?;
+      assert_eq!(from_file.read_to_string()?, streamed);
+    }
+    std::fs::remove_file(&path)?;
+    Ok(())
+  }
+}
@@ -0,0 +1,25 @@
+pub mod bench;
+pub mod days;
+pub mod determinism;
+pub mod fuzz;
+pub mod generate;
+pub mod hash;
+pub mod input;
+pub mod memo;
+pub mod minimize;
+pub mod property;
+pub mod registry;
+pub mod rng;
+pub mod rusage;
+pub mod scale;
+pub mod select;
+pub mod timing;
+pub mod tokenizer;
+pub mod trace;
+pub mod utils;
+
+#[cfg(test)]
+mod test_helper;
+
+#[doc(hidden)]
+pub type Result<T> = ::std::result::Result<T, Box<dyn ::std::error::Error>>;
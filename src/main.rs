@@ -1,30 +1,33 @@
 // #![deny(warnings)]
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use regex::Regex;
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
-mod utils;
-use utils::ReturnType;
-
-mod days;
-
-#[cfg(test)]
-mod test_helper;
-
-#[doc(hidden)]
-type Result<T> = ::std::result::Result<T, Box<dyn ::std::error::Error>>;
-
-/// Command signature
-/// # Argument
-/// * `filename` - filename containing problem input
-type CommandFunction = fn(filename: &Path) -> Result<ReturnType>;
+use aoc_2022::days::day01;
+use aoc_2022::days::day04::{self, Relation};
+use aoc_2022::days::day05::{self, CraneModel, LimitedCapacity};
+use aoc_2022::determinism;
+use aoc_2022::generate;
+use aoc_2022::input::Input;
+use aoc_2022::minimize;
+use aoc_2022::property;
+use aoc_2022::registry::{self, Solver};
+use aoc_2022::rusage::Usage;
+use aoc_2022::scale;
+use aoc_2022::timing::{self, format_nanos, CycleSource, Timer};
+use aoc_2022::trace;
+use aoc_2022::Result;
 
 #[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
+#[clap(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
+  #[clap(subcommand)]
+  command: Option<Command>,
+
   /// Filename or directory containing input files
   input: Option<String>,
 
@@ -35,54 +38,222 @@ struct Args {
   /// Filter to exclude matching command. No filter if missing.
   #[clap(short = 'e', long = "exclude")]
   filter_exclusion: Option<String>,
+
+  /// Map input files in memory instead of reading them (unix only)
+  #[clap(long)]
+  mmap: bool,
+
+  /// Let each command read the input file itself instead of sharing a buffer loaded once
+  #[clap(long)]
+  no_cache: bool,
+
+  /// Also print cycles per input byte, from the time stamp counter on x86_64 and estimated elsewhere
+  #[clap(long)]
+  cycles: bool,
+
+  /// Also print the cpu time, max RSS, context switches and page faults of each command (getrusage)
+  #[clap(long)]
+  rusage: bool,
+
+  /// Write the timeline of the commands and of their phases as Chrome trace events to this file
+  #[clap(long)]
+  trace_out: Option<PathBuf>,
 }
 
-/// Macro to register command with
-/// # Arguments
-/// * `func` - function that take file and output part1 and part2 result
-/// # Example
-/// ```
-/// let register = register_command!(day01::day01, day01::day01functional);
-/// ```
-#[macro_export]
-macro_rules! register_command {
-    ( $( $func:expr),+ ) => {
-        {
-          // Intermediate variable to force type. otherwise function type is not generic
-          let reg: Vec<(&str, CommandFunction)> = vec![
-            $((stringify!($func), $func),)*
-          ];
-          reg
-        }
-    };
+#[derive(Subcommand, Debug)]
+enum Command {
+  /// Write a random valid input for a day
+  Generate {
+    day: u32,
+
+    /// Same seed gives the same input
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Size of the input, its meaning depends on the day (lines, elves, monkeys, grid width, ...)
+    #[clap(long)]
+    size: Option<usize>,
+
+    /// Use the example parameters when they differ from the real input (day15), name the file with `test`
+    #[clap(long)]
+    example: bool,
+
+    /// Output file, stdout if missing
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+  },
+  /// Check that all the variants of a day agree on generated inputs, all checkable days if missing
+  Check {
+    day: Option<u32>,
+
+    /// Number of generated inputs per day
+    #[clap(long, default_value_t = 100)]
+    cases: u32,
+
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Largest input size tried, the size of a real input if missing
+    #[clap(long)]
+    max_size: Option<usize>,
+  },
+  /// Per-elf report of a day01 input (index, item count, total, rank) and the calories of the top N elves
+  Elves {
+    input: PathBuf,
+
+    /// Number of elves summed
+    #[clap(long, default_value_t = 3)]
+    top: usize,
+
+    /// Print the report as CSV
+    #[clap(long)]
+    csv: bool,
+  },
+  /// Replay the moves of a day05 input one at a time and draw the stacks for both crane models
+  Replay {
+    input: PathBuf,
+
+    /// Draw the stacks every N moves (and after the last one)
+    #[clap(long, default_value_t = 1)]
+    every: usize,
+
+    /// Only draw the stacks after this move, 0 is the initial diagram
+    #[clap(long)]
+    step: Option<usize>,
+
+    /// Print the stacks that differ between two steps
+    #[clap(long, number_of_values = 2, value_names = &["FROM", "TO"])]
+    diff: Option<Vec<usize>>,
+
+    /// Also replay with a crane lifting at most this number of crates at a time, can be repeated
+    #[clap(long)]
+    capacity: Vec<usize>,
+  },
+  /// Answers of a day05 input for several crane models in one pass, with their lifts and distance
+  Cranes {
+    input: PathBuf,
+
+    /// Also simulate a crane lifting at most this number of crates at a time, can be repeated
+    #[clap(long)]
+    capacity: Vec<usize>,
+  },
+  /// Histogram of the interval relations (Allen's algebra) between the pairs of elves of a day04 input
+  Relations { input: PathBuf },
+  /// Run the variants of a day several times with different hasher seeds and on equivalent inputs
+  /// (shuffled lines, mirrored grids, ...), report the answers that change. All days if missing
+  Determinism {
+    day: Option<u32>,
+
+    /// Number of generated inputs per day
+    #[clap(long, default_value_t = 5)]
+    inputs: u32,
+
+    /// Runs of each variant on each input
+    #[clap(long, default_value_t = 10)]
+    runs: u32,
+
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Size of the generated inputs, the size of a real input if missing
+    #[clap(long)]
+    size: Option<usize>,
+  },
+  /// Reduce an input while it still fails, to get a small test case
+  Minimize {
+    day: u32,
+
+    input: PathBuf,
+
+    /// What makes the input fail: crash, error, mismatch (between variants), part1 or part2 (mismatch on this part),
+    /// part1=<answer> or part2=<answer> (a variant gives another answer)
+    #[clap(short, long, default_value = "mismatch")]
+    predicate: minimize::Predicate,
+
+    /// Only run the variants matching this filter
+    #[clap(short = 'i', long = "include")]
+    filter_inclusion: Option<String>,
+
+    /// Output file, stdout if missing
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+  },
+  /// Time the variants of a day on generated inputs of growing size and estimate their complexity
+  Scale {
+    day: u32,
+
+    /// First generated size, a sixteenth of the real input size if missing
+    #[clap(long)]
+    min_size: Option<usize>,
+
+    /// Each size is the previous one multiplied by this factor
+    #[clap(long, default_value_t = 2.)]
+    factor: f64,
+
+    /// Number of sizes
+    #[clap(long, default_value_t = 5)]
+    steps: u32,
+
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Use the example parameters when they differ from the real input (day15)
+    #[clap(long)]
+    example: bool,
+
+    /// Only time the variants matching this filter
+    #[clap(short = 'i', long = "include")]
+    filter_inclusion: Option<String>,
+  },
+}
+
+/// What is printed with each command
+#[derive(Debug, Clone, Copy)]
+struct Report {
+  /// Cycles per input byte
+  cycles: bool,
+  /// Cpu time, memory, context switches and page faults
+  rusage: bool,
 }
 
 /// Launch and time the command execution
 /// # Arguments
 /// * `command` - command to execute
-/// * `filepath` - filename passed to the command function
+/// * `input` - input passed to the command function
 /// * `name` - name of the command
-fn measure_command_execution(command: &CommandFunction, filepath: &Path, name: &str) -> Option<u128> {
-  let now = Instant::now();
-  const NRUN: u32 = 1;
-  for _ in 0..NRUN-1 {
-    _ = command(filepath);
-  }
-  match command(filepath) {
+/// * `report` - what to print besides the time
+fn measure_command_execution(command: &Solver, input: &Input, name: &str, report: Report) -> Option<u128> {
+  let span = trace::Span::new(name.to_string(), "command").arg("input", input.path().display());
+  let usage = Usage::now();
+  let timer = Timer::start();
+  let result = command(input);
+  let timing = timer.stop();
+  span.end();
+  match result {
     Ok(result) => {
-      let duration = now.elapsed().as_micros();
-      let (part1, part2) = match result {
-        ReturnType::Numeric(part1, part2) => (format!("{}", part1), format!("{}", part2)),
-        ReturnType::String(part1, part2) => (format!("{}", part1), format!("{}", part2)),
-      };
-      println!(
-        "{: <30} in {:>8.2} us : part1={:<10} part2={:<10}",
+      let usage = usage.zip(Usage::now()).map(|(start, end)| end.since(&start));
+      let nanos = timing.nanos;
+      let bytes = input.size().unwrap_or(0);
+      let mut line = format!(
+        "{: <30} in {:>10} : part1={:<10} part2={:<10} input={} B {:>9.1} MB/s",
         name,
-        duration as f32 / NRUN as f32,
-        part1,
-        part2
+        format_nanos(nanos as f64),
+        result.part1.to_string(),
+        result.part2.to_string(),
+        bytes,
+        timing.megabytes_per_second(bytes)
       );
-      Some(duration)
+      if report.cycles {
+        line += &format!(" {:>9.2} cycles/B", timing.cycles_per_byte(bytes));
+      }
+      println!("{}", line);
+      if report.rusage {
+        match usage {
+          Some(usage) => println!("{: <30}    {}", "", usage),
+          None => println!("{: <30}    resource usage not available", ""),
+        }
+      }
+      Some(nanos)
     }
     Err(e) => {
       eprintln!("Error: in {}: {}", name, e);
@@ -91,48 +262,284 @@ fn measure_command_execution(command: &CommandFunction, filepath: &Path, name: &
   }
 }
 
+/// Inputs are opened once and shared by all commands, so they all run on the same buffer
+struct InputCache {
+  inputs: HashMap<PathBuf, Input>,
+  use_mmap: bool,
+  no_cache: bool,
+  report: Report,
+}
+
+impl InputCache {
+  fn get(&mut self, filepath: &Path) -> Result<&Input> {
+    if !self.inputs.contains_key(filepath) {
+      let span = trace::Span::new("open input", "input").arg("path", filepath.display());
+      let input = self.open(filepath)?;
+      span.end();
+      self.inputs.insert(filepath.to_path_buf(), input);
+    }
+    Ok(&self.inputs[filepath])
+  }
+
+  fn open(&self, filepath: &Path) -> Result<Input> {
+    if self.use_mmap {
+      #[cfg(unix)]
+      return Input::mmap(filepath);
+      #[cfg(not(unix))]
+      return Err("mmap is only supported on unix".into());
+    }
+    if self.no_cache {
+      Ok(Input::from_path(filepath))
+    } else {
+      Input::load(filepath)
+    }
+  }
+
+  /// Launch and time the command on the input loaded from `filepath`
+  fn measure(&mut self, command: &Solver, filepath: &Path, name: &str) -> Option<u128> {
+    let report = self.report;
+    match self.get(filepath) {
+      Ok(input) => measure_command_execution(command, input, name, report),
+      Err(e) => {
+        eprintln!("Error: in {}: {}", name, e);
+        None
+      }
+    }
+  }
+}
+
+/// Crane models of the puzzle and one with a limited capacity per `--capacity`
+fn crane_models(capacities: Vec<usize>) -> Vec<Box<dyn CraneModel>> {
+  let mut models = day05::crate_movers();
+  for capacity in capacities {
+    models.push(Box::new(LimitedCapacity(capacity)));
+  }
+  models
+}
+
+fn run_command(command: Command) -> Result<()> {
+  match command {
+    Command::Generate {
+      day,
+      seed,
+      size,
+      example,
+      output,
+    } => {
+      let input = generate::generate(day, &generate::Options { seed, size, example })?;
+      match output {
+        Some(path) => fs::write(path, input)?,
+        None => print!("{}", input),
+      }
+    }
+    Command::Check {
+      day,
+      cases,
+      seed,
+      max_size,
+    } => {
+      let config = property::Config { cases, seed, max_size };
+      let days = day.map_or_else(property::checkable_days, |day| vec![day]);
+      // Panics are reported with the counterexample, don't print them while shrinking
+      std::panic::set_hook(Box::new(|_| {}));
+      for day in days {
+        let now = Instant::now();
+        match property::check_day(day, &config)? {
+          Some(failure) => return Err(failure.to_string().into()),
+          None => println!("day{:02}: {} cases ok in {:.2} s", day, cases, now.elapsed().as_secs_f32()),
+        }
+      }
+    }
+    Command::Elves { input, top, csv } => {
+      let input = Input::load(&input)?;
+      let elves = day01::elves(&input)?;
+      if csv {
+        println!("index,items,total,rank");
+      } else {
+        println!("{:>6} {:>6} {:>10} {:>6}", "elf", "items", "total", "rank");
+      }
+      for elf in &elves {
+        if csv {
+          println!("{},{},{},{}", elf.index, elf.items, elf.total, elf.rank);
+        } else {
+          println!("{:>6} {:>6} {:>10} {:>6}", elf.index, elf.items, elf.total, elf.rank);
+        }
+      }
+      if !csv {
+        println!("top {} of {} elves: {}", top, elves.len(), day01::top_calories(&input, top)?);
+      }
+    }
+    Command::Replay {
+      input,
+      every,
+      step,
+      diff,
+      capacity,
+    } => {
+      let input = Input::load(&input)?;
+      let content = input.read_to_string()?;
+      let replay = day05::Replay::new(&content)?;
+      let models = crane_models(capacity);
+      for model in &models {
+        let model = model.as_ref();
+        println!("== {} ==", model.name());
+        if let Some(steps) = &diff {
+          let from = replay.state(steps[0], model)?;
+          print!("{}", from.diff(&replay.state(steps[1], model)?));
+        } else if let Some(step) = step {
+          let stacks = replay.state(step, model)?;
+          println!("after {}: {}", step, replay.describe(step));
+          print!("{}", stacks.render());
+        } else {
+          let last = replay.moves.len();
+          replay.run(model, |step, stacks| {
+            if step % every.max(1) == 0 || step == last {
+              println!("after {}: {}", step, replay.describe(step));
+              println!("{}", stacks.render());
+            }
+          })?;
+        }
+      }
+    }
+    Command::Cranes { input, capacity } => {
+      let input = Input::load(&input)?;
+      let content = input.read_to_string()?;
+      let (stacks, moves) = day05::parse(&content)?;
+      let models = crane_models(capacity);
+      println!("{:<16} {:>8} {:>8} {:>10} tops", "model", "lifts", "crates", "distance");
+      for simulation in day05::simulate(&stacks, &moves, models)? {
+        let cost = simulation.cost;
+        println!(
+          "{:<16} {:>8} {:>8} {:>10} {}",
+          simulation.model.name(),
+          cost.lifts,
+          cost.crates,
+          cost.distance,
+          simulation.stacks.tops()?
+        );
+      }
+    }
+    Command::Relations { input } => {
+      let histogram = day04::relations(&Input::load(&input)?)?;
+      for (relation, count) in Relation::ALL.iter().zip(histogram) {
+        let part = match (relation.is_containment(), relation.intersects()) {
+          (true, _) => "part1 part2",
+          (false, true) => "part2",
+          _ => "",
+        };
+        println!("{:<14} {:>6} {}", relation.name(), count, part);
+      }
+    }
+    Command::Determinism {
+      day,
+      inputs,
+      runs,
+      seed,
+      size,
+    } => {
+      let config = determinism::Config {
+        inputs,
+        runs,
+        seed,
+        size,
+      };
+      let days = day.map_or_else(|| generate::DAYS.to_vec(), |day| vec![day]);
+      std::panic::set_hook(Box::new(|_| {}));
+      let mut unstable = 0;
+      for day in days {
+        let now = Instant::now();
+        let findings = determinism::check_day(day, &config)?;
+        for finding in &findings {
+          println!("{}\n", finding);
+        }
+        unstable += findings.len();
+        println!(
+          "day{:02}: {} inputs x {} runs, {} unstable in {:.2} s",
+          day,
+          inputs,
+          runs,
+          findings.len(),
+          now.elapsed().as_secs_f32()
+        );
+      }
+      if unstable > 0 {
+        return Err(format!("{} unstable answers", unstable).into());
+      }
+    }
+    Command::Minimize {
+      day,
+      input,
+      predicate,
+      filter_inclusion,
+      output,
+    } => {
+      let mut variants = registry::variants(day);
+      if let Some(filter) = filter_inclusion {
+        variants.retain(|(name, _)| name.contains(&filter));
+      }
+      if variants.is_empty() {
+        return Err(format!("No solver for day {}", day).into());
+      }
+      let content = fs::read_to_string(&input)?;
+      // Panics are part of the outcomes, don't print them for each try
+      std::panic::set_hook(Box::new(|_| {}));
+      let minimized = minimize::minimize(day, &variants, &input, &content, &predicate)?;
+      match output {
+        Some(path) => fs::write(path, &minimized.content)?,
+        None => print!("{}", minimized.content),
+      }
+      eprintln!(
+        "minimized from {} to {} bytes in {} runs",
+        content.len(),
+        minimized.content.len(),
+        minimized.runs
+      );
+      for (name, outcome) in &minimized.outcomes {
+        eprintln!("  {: <30} {}", name, outcome);
+      }
+      eprintln!("\n{}", minimized.test_case(day, &input, &predicate)?);
+    }
+    Command::Scale {
+      day,
+      min_size,
+      factor,
+      steps,
+      seed,
+      example,
+      filter_inclusion,
+    } => {
+      let mut variants = registry::variants(day);
+      if let Some(filter) = filter_inclusion {
+        variants.retain(|(name, _)| name.contains(&filter));
+      }
+      if variants.is_empty() {
+        return Err(format!("No solver for day {}", day).into());
+      }
+      let config = scale::Config {
+        min_size,
+        factor,
+        steps,
+        seed,
+        example,
+      };
+      let series = scale::measure(day, &variants, &config)?;
+      print!("{}", scale::Report { day, series });
+    }
+  }
+  Ok(())
+}
+
 fn main() {
-  let register = register_command!(
-    days::day01::day01,
-    days::day01::day01_speed,
-    days::day02::day02,
-    days::day02::day02_speed,
-    days::day03::day03,
-    days::day03::day03_speed,
-    days::day04::day04,
-    days::day04::day04_speed,
-    days::day05::day05,
-    days::day05::day05_speed,
-    days::day06::day06,
-    days::day06::day06_speed,
-    days::day07::day07,
-    days::day08::day08,
-    days::day08::day08_speed,
-    days::day09::day09,
-    days::day09::day09_speed,
-    days::day10::day10,
-    days::day11::day11,
-    days::day11::day11_speed,
-    days::day12::day12,
-    days::day12::day12_speed,
-    // days::day13::day13,
-    days::day14::day14,
-    days::day15::day15,
-    days::day15::day15_speed,
-    days::day15::day15_only_range,
-    days::day16::day16,
-    days::day17::day17,
-    days::day18::day18
-    // days::day19::day19,
-    // days::day20::day20,
-    // days::day21::day21,
-    // days::day22::day22,
-    // days::day23::day23,
-    // days::day24::day24,
-    // days::day25::day25
-  );
+  let register = registry::all();
 
   let args = Args::parse();
+  if let Some(command) = args.command {
+    if let Err(e) = run_command(command) {
+      eprintln!("Error: {}", e);
+      std::process::exit(1);
+    }
+    return;
+  }
   let input_filename = args.input.unwrap_or(String::from("data"));
   let input_path = Path::new(&input_filename);
 
@@ -141,7 +548,27 @@ fn main() {
     return;
   }
 
+  if args.trace_out.is_some() {
+    trace::enable();
+  }
   let mut total_time = 0u128;
+  let mut inputs = InputCache {
+    inputs: HashMap::new(),
+    use_mmap: args.mmap,
+    no_cache: args.no_cache,
+    report: Report {
+      cycles: args.cycles,
+      rusage: args.rusage,
+    },
+  };
+  if args.cycles {
+    let (frequency, source) = timing::frequency();
+    let source = match source {
+      CycleSource::TimeStampCounter => "time stamp counter",
+      CycleSource::Estimated => "estimated from the time",
+    };
+    println!("cycles at {:.2} GHz ({})", frequency, source);
+  }
 
   // Apply commands to given file
   if input_path.is_file() {
@@ -158,7 +585,7 @@ fn main() {
         }
       }
 
-      if let Some(duration) = measure_command_execution(command, input_path, name) {
+      if let Some(duration) = inputs.measure(command, input_path, name) {
         total_time += duration;
       }
     }
@@ -202,7 +629,7 @@ fn main() {
             Some(filepath)
           })
           .for_each(|filepath| {
-            if let Some(duration) = measure_command_execution(command, &filepath.path(), name) {
+            if let Some(duration) = inputs.measure(command, &filepath.path(), name) {
               total_time += duration;
             }
           });
@@ -210,5 +637,10 @@ fn main() {
     }
   }
 
-  println!("Total time : {:>7.2} ms", total_time as f32 / 1000.)
+  println!("Total time : {:>7.2} ms", total_time as f64 / 1e6);
+  if let Some(path) = args.trace_out {
+    if let Err(e) = trace::write(&path) {
+      eprintln!("Error: can't write the trace to {}: {}", path.display(), e);
+    }
+  }
 }
@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+
+use crate::hash::FastBuildHasher;
+
+/// Cache for recursive searches
+/// The hasher is pluggable and default to [`FastBuildHasher`], keys should be small `Copy` values
+/// like [`BitSet64`] or integers packed with [`pack`].
+/// With a capacity limit the whole table is dropped when it's full, it's crude but keep lookup cost unchanged.
+/// # Example
+/// ```
+/// use aoc_2022::memo::Memo;
+///
+/// fn fibonacci(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+///   if n < 2 {
+///     return n;
+///   }
+///   memo.compute(n, |memo| fibonacci(n - 1, memo) + fibonacci(n - 2, memo))
+/// }
+///
+/// let mut memo = Memo::new();
+/// assert_eq!(fibonacci(90, &mut memo), 2880067194370816120);
+/// assert_eq!(memo.stats().misses, 89);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Memo<K, V, S = FastBuildHasher> {
+  cache: HashMap<K, V, S>,
+  capacity: Option<usize>,
+  stats: MemoStats,
+}
+
+/// Hit/miss counters of a [`Memo`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoStats {
+  pub hits: u64,
+  pub misses: u64,
+  /// number of entries dropped because the capacity limit was reached
+  pub evictions: u64,
+}
+
+impl MemoStats {
+  pub fn hit_rate(&self) -> f64 {
+    if self.hits + self.misses == 0 {
+      0.
+    } else {
+      self.hits as f64 / (self.hits + self.misses) as f64
+    }
+  }
+}
+
+impl std::fmt::Display for MemoStats {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(
+      f,
+      "hits={} misses={} evictions={} hit_rate={:.1}%",
+      self.hits,
+      self.misses,
+      self.evictions,
+      self.hit_rate() * 100.
+    )
+  }
+}
+
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+  pub fn new() -> Self {
+    Self::with_hasher(FastBuildHasher::default())
+  }
+
+  /// Memo that never hold more than `capacity` entries
+  pub fn bounded(capacity: usize) -> Self {
+    Self {
+      capacity: Some(capacity),
+      ..Self::new()
+    }
+  }
+}
+
+impl<K: Hash + Eq, V: Clone> Default for Memo<K, V> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<K: Hash + Eq, V: Clone, S: BuildHasher> Memo<K, V, S> {
+  pub fn with_hasher(hasher: S) -> Self {
+    Self {
+      cache: HashMap::with_hasher(hasher),
+      capacity: None,
+      stats: MemoStats::default(),
+    }
+  }
+
+  /// Look for a cached value, a lookup count as a hit or a miss in the statistics
+  #[inline]
+  pub fn get(&mut self, key: &K) -> Option<V> {
+    match self.cache.get(key) {
+      Some(value) => {
+        self.stats.hits += 1;
+        Some(value.clone())
+      }
+      None => {
+        self.stats.misses += 1;
+        None
+      }
+    }
+  }
+
+  #[inline]
+  pub fn insert(&mut self, key: K, value: V) {
+    if let Some(capacity) = self.capacity {
+      if self.cache.len() >= capacity {
+        self.stats.evictions += self.cache.len() as u64;
+        self.cache.clear();
+      }
+    }
+    self.cache.insert(key, value);
+  }
+
+  /// Return the cached value or compute it with `func`
+  /// The memo is given back to `func` so it can be used for recursive call.
+  #[inline]
+  pub fn compute(&mut self, key: K, func: impl FnOnce(&mut Self) -> V) -> V {
+    if let Some(value) = self.get(&key) {
+      return value;
+    }
+    let value = func(self);
+    self.insert(key, value.clone());
+    value
+  }
+
+  pub fn len(&self) -> usize {
+    self.cache.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.cache.is_empty()
+  }
+
+  pub fn stats(&self) -> MemoStats {
+    self.stats
+  }
+
+  /// Drop all entries, statistics are kept
+  pub fn clear(&mut self) {
+    self.cache.clear();
+  }
+}
+
+/// Set of small integers (< 64) stored in a single u64
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitSet64(pub u64);
+
+impl BitSet64 {
+  pub const CAPACITY: usize = 64;
+
+  pub fn new() -> Self {
+    Self(0)
+  }
+
+  /// Set containing all values in 0..count
+  pub fn full(count: usize) -> Self {
+    debug_assert!(count <= Self::CAPACITY);
+    if count >= Self::CAPACITY {
+      Self(u64::MAX)
+    } else {
+      Self((1 << count) - 1)
+    }
+  }
+
+  #[inline(always)]
+  pub fn contains(&self, value: usize) -> bool {
+    debug_assert!(value < Self::CAPACITY);
+    self.0 & (1 << value) != 0
+  }
+
+  #[inline(always)]
+  pub fn insert(&mut self, value: usize) {
+    debug_assert!(value < Self::CAPACITY);
+    self.0 |= 1 << value;
+  }
+
+  #[inline(always)]
+  pub fn remove(&mut self, value: usize) {
+    debug_assert!(value < Self::CAPACITY);
+    self.0 &= !(1 << value);
+  }
+
+  /// Copy of the set without `value`
+  #[inline(always)]
+  pub fn without(self, value: usize) -> Self {
+    debug_assert!(value < Self::CAPACITY);
+    Self(self.0 & !(1 << value))
+  }
+
+  #[inline(always)]
+  pub fn len(&self) -> usize {
+    self.0.count_ones() as usize
+  }
+
+  #[inline(always)]
+  pub fn is_empty(&self) -> bool {
+    self.0 == 0
+  }
+
+  /// Iterate on values in increasing order
+  pub fn iter(&self) -> impl Iterator<Item = usize> {
+    let mut bits = self.0;
+    std::iter::from_fn(move || {
+      if bits == 0 {
+        return None;
+      }
+      let value = bits.trailing_zeros() as usize;
+      bits &= bits - 1;
+      Some(value)
+    })
+  }
+}
+
+impl FromIterator<usize> for BitSet64 {
+  fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+    let mut set = Self::new();
+    for value in iter {
+      set.insert(value);
+    }
+    set
+  }
+}
+
+/// Pack several small fields into a single u64 key
+/// Each field is given as `(value, bit width)`, the first field end in the lowest bits.
+/// # Example
+/// ```
+/// use aoc_2022::memo::pack;
+/// assert_eq!(pack([(0b11, 2), (0b1, 1)]), 0b111);
+/// ```
+#[inline(always)]
+pub fn pack<const N: usize>(fields: [(u64, u32); N]) -> u64 {
+  debug_assert!(fields.iter().map(|(_, width)| width).sum::<u32>() <= 64);
+  let mut key = 0;
+  let mut shift = 0;
+  for (value, width) in fields {
+    debug_assert!(width == 64 || value >> width == 0, "{} doesn't fit in {} bits", value, width);
+    key |= value << shift;
+    shift += width;
+  }
+  key
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn memo_bounded_capacity() {
+    let mut memo = Memo::bounded(2);
+    memo.insert(1, 10);
+    memo.insert(2, 20);
+    assert_eq!(memo.get(&1), Some(10));
+    memo.insert(3, 30);
+    assert_eq!(memo.len(), 1);
+    assert_eq!(memo.get(&1), None);
+    assert_eq!(memo.get(&3), Some(30));
+    assert_eq!(
+      memo.stats(),
+      MemoStats {
+        hits: 2,
+        misses: 1,
+        evictions: 2
+      }
+    );
+  }
+
+  #[test]
+  fn bitset_iter() {
+    let set = [1, 5, 63].into_iter().collect::<BitSet64>();
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![1, 5, 63]);
+    assert_eq!(set.without(5).len(), 2);
+    assert!(!set.contains(2));
+    assert_eq!(BitSet64::full(64).len(), 64);
+  }
+
+  #[test]
+  fn pack_fields() {
+    assert_eq!(pack([(3, 8), (7, 8), (1, 48)]), 3 | 7 << 8 | 1 << 16);
+  }
+}
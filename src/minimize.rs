@@ -0,0 +1,451 @@
+//! Delta debugging of a failing input: remove pieces of the input while the failure is still there.
+//! Pieces follow the structure of each day (monkey blocks for day11, crate rows and moves for day05, ...).
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use crate::input::Input;
+use crate::property::{self, Outcome};
+use crate::registry::Solver;
+use crate::utils::{Answer, ReturnType};
+use crate::Result;
+
+/// What makes an input fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Predicate {
+  /// One of the variants panics
+  Crash,
+  /// One of the variants returns an error
+  Error,
+  /// Variants don't give the same answer, or one answers when another returns an error
+  Mismatch,
+  /// Variants give different answers for this part
+  Part(u32),
+  /// A variant answers something else than `value` for this part, works with a single variant
+  Expected { part: u32, value: Answer },
+}
+
+/// Answer of a part
+fn select(result: &ReturnType, part: u32) -> &Answer {
+  match part {
+    1 => &result.part1,
+    _ => &result.part2,
+  }
+}
+
+impl Predicate {
+  pub fn holds(&self, outcomes: &[(&str, Outcome)]) -> bool {
+    let answers = || {
+      outcomes.iter().filter_map(|(_, outcome)| match outcome {
+        Outcome::Answer(result) => Some(result),
+        _ => None,
+      })
+    };
+    match self {
+      Predicate::Crash => outcomes.iter().any(|(_, outcome)| matches!(outcome, Outcome::Panic(_))),
+      Predicate::Error => outcomes.iter().any(|(_, outcome)| matches!(outcome, Outcome::Error(_))),
+      Predicate::Mismatch => {
+        let errors = outcomes.iter().any(|(_, outcome)| matches!(outcome, Outcome::Error(_)));
+        let mut answers = answers();
+        match answers.next() {
+          Some(first) => errors || answers.any(|other| other != first),
+          None => false,
+        }
+      }
+      Predicate::Part(part) => {
+        let mut answers = answers().map(|result| select(result, *part));
+        match answers.next() {
+          Some(first) => answers.any(|other| other != first),
+          None => false,
+        }
+      }
+      Predicate::Expected { part, value } => answers().any(|result| select(result, *part) != value),
+    }
+  }
+
+  /// Expected value with the type the variants answer for its part, `part1=123` is parsed as a number but day05
+  /// answers text. A text value for a numeric part can never match and is an error.
+  pub fn typed(&self, outcomes: &[(&str, Outcome)]) -> Result<Predicate> {
+    let Predicate::Expected { part, value } = self else {
+      return Ok(self.clone());
+    };
+    let answer = outcomes.iter().find_map(|(name, outcome)| match outcome {
+      Outcome::Answer(result) => Some((name, select(result, *part))),
+      _ => None,
+    });
+    let value = match (answer, value) {
+      (Some((_, Answer::Text(_))), Answer::Numeric(number)) => Answer::Text(number.to_string()),
+      (Some((_, Answer::Numeric(_))), Answer::Text(text)) => {
+        return Err(format!("Part{} answers a number, {} is expected", part, text).into());
+      }
+      (Some((name, Answer::Unsolved)), _) => return Err(format!("Part{} isn't solved by {}", part, name).into()),
+      _ => value.clone(),
+    };
+    Ok(Predicate::Expected { part: *part, value })
+  }
+}
+
+impl std::str::FromStr for Predicate {
+  type Err = String;
+
+  fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+    match value {
+      "crash" => Ok(Predicate::Crash),
+      "error" => Ok(Predicate::Error),
+      "mismatch" => Ok(Predicate::Mismatch),
+      "part1" => Ok(Predicate::Part(1)),
+      "part2" => Ok(Predicate::Part(2)),
+      _ => match value.split_once('=') {
+        // the expected answer, `part1=1234` or `part2=ZWHVFWQWW`
+        Some((part @ ("part1" | "part2"), expected)) => Ok(Predicate::Expected {
+          part: if part == "part1" { 1 } else { 2 },
+          value: expected
+            .parse::<u64>()
+            .map_or_else(|_| Answer::Text(expected.to_string()), Answer::Numeric),
+        }),
+        _ => Err(format!(
+          "Unknown predicate {}, expected crash, error, mismatch, part1, part2, part1=<answer> or part2=<answer>",
+          value
+        )),
+      },
+    }
+  }
+}
+
+/// Size of the pieces removed in a pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Granularity {
+  /// Blank line separated blocks
+  Blocks,
+  Lines,
+  /// Characters of single line inputs
+  Chars,
+}
+
+/// Passes run for a day, from the biggest pieces to the smallest
+fn passes(day: u32) -> &'static [Granularity] {
+  match day {
+    1 => &[Granularity::Blocks, Granularity::Lines],
+    // a monkey is only valid as a whole block
+    11 => &[Granularity::Blocks],
+    6 | 17 => &[Granularity::Chars],
+    _ => &[Granularity::Lines],
+  }
+}
+
+/// Lines the day can't parse without: stack indexes and the blank line before the moves of day05
+fn is_structural(day: u32, line: &str) -> bool {
+  match day {
+    5 => line.trim().is_empty() || line.trim_start().starts_with(|c: char| c.is_ascii_digit()),
+    _ => false,
+  }
+}
+
+/// Pieces of the input with a flag telling if they can be removed, their concatenation is the input
+fn split(day: u32, content: &str, granularity: Granularity) -> Vec<(&str, bool)> {
+  let mut pieces = Vec::new();
+  match granularity {
+    Granularity::Blocks => {
+      let mut rest = content;
+      while !rest.is_empty() {
+        let end = rest.find("\n\n").map_or(rest.len(), |index| index + 2);
+        pieces.push((&rest[..end], true));
+        rest = &rest[end..];
+      }
+    }
+    Granularity::Lines => {
+      for line in content.split_inclusive('\n') {
+        pieces.push((line, !is_structural(day, line)));
+      }
+    }
+    Granularity::Chars => {
+      let end = content.trim_end().len();
+      for (index, c) in content[..end].char_indices() {
+        pieces.push((&content[index..index + c.len_utf8()], true));
+      }
+      pieces.push((&content[end..], false));
+    }
+  }
+  pieces
+}
+
+/// Classic ddmin: try to remove chunks of `removable`, halving the chunk size when nothing can be removed.
+/// `fails` is called with the indexes kept.
+fn ddmin(removable: Vec<usize>, mut fails: impl FnMut(&[usize]) -> Result<bool>) -> Result<Vec<usize>> {
+  let mut kept = removable;
+  let mut chunks = 2;
+  while kept.len() >= 2 || (kept.len() == 1 && chunks <= 2) {
+    let chunk_size = kept.len().div_ceil(chunks);
+    let mut reduced = false;
+    for start in (0..kept.len()).step_by(chunk_size.max(1)) {
+      let candidate = kept
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index < start || *index >= start + chunk_size)
+        .map(|(_, piece)| *piece)
+        .collect::<Vec<_>>();
+      if fails(&candidate)? {
+        kept = candidate;
+        chunks = (chunks - 1).max(2);
+        reduced = true;
+        break;
+      }
+    }
+    if !reduced {
+      if chunk_size <= 1 {
+        break;
+      }
+      chunks = (chunks * 2).min(kept.len());
+    }
+  }
+  Ok(kept)
+}
+
+/// Result of a minimization
+#[derive(Debug, Clone)]
+pub struct Minimized {
+  pub content: String,
+  /// Number of times the solvers were run
+  pub runs: usize,
+  pub outcomes: Vec<(&'static str, Outcome)>,
+}
+
+/// Run `variants` on `content` with the name of the original file (day15 looks for `test` in it)
+fn run_all(variants: &[(&'static str, Solver)], path: &Path, content: &str) -> Vec<(&'static str, Outcome)> {
+  let input = Input::from_bytes(path, content.as_bytes().to_vec());
+  variants
+    .iter()
+    .map(|(name, solver)| (*name, property::run(*solver, &input)))
+    .collect()
+}
+
+/// Smallest input found that still fails with `fails`, the original `content` must fail
+pub fn minimize_with(
+  day: u32,
+  variants: &[(&'static str, Solver)],
+  path: &Path,
+  content: &str,
+  mut fails: impl FnMut(&[(&str, Outcome)]) -> bool,
+) -> Result<Minimized> {
+  let mut outcomes = run_all(variants, path, content);
+  if !fails(&outcomes) {
+    return Err("The input doesn't fail, nothing to minimize".into());
+  }
+  let mut current = content.to_string();
+  let mut runs = 1;
+  loop {
+    let size = current.len();
+    for granularity in passes(day) {
+      let pieces = split(day, &current, *granularity);
+      let removable = (0..pieces.len()).filter(|index| pieces[*index].1).collect::<Vec<_>>();
+      let rebuild = |kept: &[usize]| {
+        pieces
+          .iter()
+          .enumerate()
+          .filter(|(index, (_, can_remove))| !can_remove || kept.binary_search(index).is_ok())
+          .map(|(_, (piece, _))| *piece)
+          .collect::<String>()
+      };
+      let kept = ddmin(removable, |kept| {
+        let candidate = rebuild(kept);
+        runs += 1;
+        let candidate_outcomes = run_all(variants, path, &candidate);
+        if fails(&candidate_outcomes) {
+          outcomes = candidate_outcomes;
+          return Ok(true);
+        }
+        Ok(false)
+      })?;
+      current = rebuild(&kept);
+    }
+    if current.len() >= size {
+      break;
+    }
+  }
+  Ok(Minimized {
+    content: current,
+    runs,
+    outcomes,
+  })
+}
+
+pub fn minimize(
+  day: u32,
+  variants: &[(&'static str, Solver)],
+  path: &Path,
+  content: &str,
+  predicate: &Predicate,
+) -> Result<Minimized> {
+  let predicate = predicate.typed(&run_all(variants, path, content))?;
+  minimize_with(day, variants, path, content, |outcomes| predicate.holds(outcomes))
+}
+
+/// `add_test!` literal of an answer
+fn literal(answer: &Answer) -> String {
+  match answer {
+    Answer::Numeric(value) => value.to_string(),
+    Answer::Text(value) => format!("{:?}", value),
+    // full path, the test modules don't all import it
+    Answer::Unsolved => String::from("crate::utils::Unsolved"),
+  }
+}
+
+/// Raw string literal of `content`, with as many `#` as needed for the quotes it contains
+fn raw_string(content: &str) -> String {
+  let mut hashes = String::new();
+  while content.contains(&format!("\"{}", hashes)) {
+    hashes.push('#');
+  }
+  format!("r{0}\"{1}\"{0}", hashes, content)
+}
+
+impl Minimized {
+  /// Answer of a part all the answering variants agree on.
+  /// `Unsolved` when they disagree, one of them is wrong and its answer mustn't become the expectation.
+  fn agreed(&self, part: u32) -> Option<Answer> {
+    let mut answers = self.outcomes.iter().filter_map(|(_, outcome)| match outcome {
+      Outcome::Answer(result) if part == 1 => Some(&result.part1),
+      Outcome::Answer(result) => Some(&result.part2),
+      _ => None,
+    });
+    let first = answers.next()?;
+    Some(match answers.all(|other| other == first) {
+      true => first.clone(),
+      false => Answer::Unsolved,
+    })
+  }
+
+  /// Test case to paste in the day module, the part of an `Expected` predicate expects its value.
+  /// A part the variants disagree on is expected `Unsolved` and has to be filled by hand.
+  pub fn test_case(&self, day: u32, path: &Path, predicate: &Predicate) -> Result<String> {
+    let mut out = String::new();
+    let name = PathBuf::from(path)
+      .file_stem()
+      .and_then(|stem| stem.to_str())
+      .map_or(String::from("minimized"), |stem| {
+        stem.replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+      });
+    writeln!(out, "  const MINIMIZED: &str = {};", raw_string(&self.content))?;
+    let predicate = predicate.typed(&self.outcomes)?;
+    let expected = |part: u32| match &predicate {
+      Predicate::Expected { part: expected, value } if *expected == part => Some(value.clone()),
+      _ => self.agreed(part),
+    };
+    let (part1, part2) = (expected(1), expected(2));
+    if self.agreed(1).is_none() {
+      writeln!(
+        out,
+        "    // no variant answers, the test fails with their error until they are fixed"
+      )?;
+    }
+    let part1 = literal(&part1.unwrap_or(Answer::Unsolved));
+    let part2 = literal(&part2.unwrap_or(Answer::Unsolved));
+    for (variant, _) in &self.outcomes {
+      let function = variant.rsplit("::").next().unwrap_or(variant);
+      writeln!(
+        out,
+        "    {}: {}, Example(MINIMIZED), [{}, {}];",
+        name, function, part1, part2
+      )?;
+    }
+    if day == 15 && !name.contains("test") {
+      writeln!(
+        out,
+        "  // day15 uses the example search area only when the test name contains `test`"
+      )?;
+    }
+    Ok(out)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::registry;
+  use crate::utils::Solution;
+
+  #[test]
+  fn ddmin_keeps_the_needed_pieces() -> Result<()> {
+    let kept = ddmin((0..20).collect(), |kept| Ok(kept.contains(&3) && kept.contains(&17)))?;
+    assert_eq!(kept, vec![3, 17]);
+    Ok(())
+  }
+
+  #[test]
+  fn minimize_keeps_structure() -> Result<()> {
+    // fail as long as the third stack ends with P, everything but the stack indexes can go
+    let content = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3\n\nmove 1 from 2 to 1\nmove 1 from 1 to 2\n";
+    let variants = registry::variants(5);
+    let minimized = minimize_with(
+      5,
+      &variants,
+      Path::new("day05.txt"),
+      content,
+      |outcomes| matches!(&outcomes[0].1, Outcome::Answer(result) if result.part1.to_string().ends_with('P')),
+    )?;
+    assert_eq!(minimized.content, "[Z] [M] [P]\n 1   2   3\n\n");
+    Ok(())
+  }
+
+  #[test]
+  fn test_case_compiles() -> Result<()> {
+    let answer = |part1: u64, part2: u64| Outcome::Answer(Solution::new(part1, part2).into());
+    let mut minimized = Minimized {
+      content: String::from("a \"quoted\"# line\n"),
+      runs: 1,
+      outcomes: vec![
+        ("days::day01::day01", answer(3, 4)),
+        ("days::day01::day01_speed", answer(3, 5)),
+      ],
+    };
+    let case = minimized.test_case(1, Path::new("day01.txt"), &Predicate::Mismatch)?;
+    assert!(case.contains("r##\"a \"quoted\"# line\n\"##;"), "{}", case);
+    // the disputed part isn't frozen
+    assert!(
+      case.contains("day01: day01, Example(MINIMIZED), [3, crate::utils::Unsolved];"),
+      "{}",
+      case
+    );
+    minimized.outcomes = vec![("days::day01::day01", Outcome::Error(String::from("No elf")))];
+    let case = minimized.test_case(1, Path::new("day01.txt"), &Predicate::Mismatch)?;
+    assert!(
+      case.contains("[crate::utils::Unsolved, crate::utils::Unsolved];"),
+      "{}",
+      case
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn expected_answer_on_a_single_variant() -> Result<()> {
+    // day18 has one variant, a wrong expectation of part1 still has something to minimize
+    let predicate = "part1=0".parse::<Predicate>()?;
+    let variants = registry::variants(18);
+    let content = "2,2,2\n1,2,2\n3,2,2\n2,1,2\n2,3,2\n";
+    let minimized = minimize(18, &variants, Path::new("day18.txt"), content, &predicate)?;
+    assert_eq!(minimized.content.lines().count(), 1);
+    let case = minimized.test_case(18, Path::new("day18.txt"), &predicate)?;
+    assert!(
+      case.contains("day18: day18, Example(MINIMIZED), [0, crate::utils::Unsolved];"),
+      "{}",
+      case
+    );
+    assert!("part3=1".parse::<Predicate>().is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn expected_answer_takes_the_part_type() -> Result<()> {
+    // day05 answers text, a numeric looking expectation is compared and emitted as text
+    let content = "[1]\n 1 \n\nmove 1 from 1 to 1\n";
+    let variants = registry::variants(5);
+    let predicate = "part1=2".parse::<Predicate>()?;
+    let minimized = minimize(5, &variants, Path::new("day05.txt"), content, &predicate)?;
+    let case = minimized.test_case(5, Path::new("day05.txt"), &predicate)?;
+    assert!(case.contains("Example(MINIMIZED), [\"2\", \"1\"];"), "{}", case);
+    // a text expectation can't match a numeric part
+    let variants = registry::variants(18);
+    let error = minimize(18, &variants, Path::new("day18.txt"), "1,1,1\n", &"part1=abc".parse()?).unwrap_err();
+    assert!(error.to_string().contains("answers a number"), "{}", error);
+    Ok(())
+  }
+}
@@ -0,0 +1,207 @@
+//! Differential testing: every variant of a day must give the same answer on generated inputs.
+//! A failing case is shrunk to the smallest generated input that still fails.
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::generate;
+use crate::input::Input;
+use crate::minimize;
+use crate::registry::{self, Solver};
+use crate::rng::Rng;
+use crate::utils::ReturnType;
+use crate::Result;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+  Answer(ReturnType),
+  Error(String),
+  Panic(String),
+}
+
+impl fmt::Display for Outcome {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Outcome::Answer(result) => write!(f, "part1={} part2={}", result.part1, result.part2),
+      Outcome::Error(e) => write!(f, "error: {}", e),
+      Outcome::Panic(message) => write!(f, "panic: {}", message),
+    }
+  }
+}
+
+/// Run a solver, turning errors and panics into an `Outcome`
+pub fn run(solver: Solver, input: &Input) -> Outcome {
+  match catch_unwind(AssertUnwindSafe(|| solver(input))) {
+    Ok(Ok(result)) => Outcome::Answer(result),
+    Ok(Err(e)) => Outcome::Error(e.to_string()),
+    Err(payload) => {
+      let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("unknown panic"));
+      Outcome::Panic(message)
+    }
+  }
+}
+
+/// Variants agree when they all give the same answer, or all reject the input.
+/// A panic is never fine.
+pub fn agree(outcomes: &[(&str, Outcome)]) -> bool {
+  if outcomes.iter().any(|(_, outcome)| matches!(outcome, Outcome::Panic(_))) {
+    return false;
+  }
+  if outcomes.iter().all(|(_, outcome)| matches!(outcome, Outcome::Error(_))) {
+    return true;
+  }
+  outcomes.windows(2).all(|pair| pair[0].1 == pair[1].1)
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+  /// Number of generated inputs per day
+  pub cases: u32,
+  pub seed: u64,
+  /// Upper bound of the input size, the day default size if missing
+  pub max_size: Option<usize>,
+}
+
+impl Default for Config {
+  fn default() -> Self {
+    Self {
+      cases: 100,
+      seed: 0,
+      max_size: None,
+    }
+  }
+}
+
+/// Generated input on which the variants of a day disagree
+#[derive(Debug, Clone)]
+pub struct Counterexample {
+  pub day: u32,
+  pub seed: u64,
+  pub size: usize,
+  pub input: String,
+  pub outcomes: Vec<(&'static str, Outcome)>,
+  /// Generated input reduced further by removing lines, if it could be
+  pub minimized: Option<String>,
+}
+
+impl fmt::Display for Counterexample {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    writeln!(
+      f,
+      "day{:02} variants disagree (seed={} size={})",
+      self.day, self.seed, self.size
+    )?;
+    for (name, outcome) in &self.outcomes {
+      writeln!(f, "  {: <30} {}", name, outcome)?;
+    }
+    writeln!(
+      f,
+      "reproduce with: generate {} --seed {} --size {} --example",
+      self.day, self.seed, self.size
+    )?;
+    match &self.minimized {
+      Some(minimized) => write!(f, "minimized input:\n{}", minimized),
+      None => write!(f, "input:\n{}", self.input),
+    }
+  }
+}
+
+/// Generate the input for (`seed`, `size`), run all `variants` on it and return it if they disagree
+fn try_case(day: u32, variants: &[(&'static str, Solver)], seed: u64, size: usize) -> Result<Option<Counterexample>> {
+  // `test` in the name select the example parameters of day15
+  let options = generate::Options {
+    seed,
+    size: Some(size),
+    example: true,
+  };
+  let content = generate::generate(day, &options)?;
+  let input = Input::from_bytes(format!("day{:02}_test_property", day), content.clone().into_bytes());
+  let outcomes = variants
+    .iter()
+    .map(|(name, solver)| (*name, run(*solver, &input)))
+    .collect::<Vec<_>>();
+  if agree(&outcomes) {
+    return Ok(None);
+  }
+  Ok(Some(Counterexample {
+    day,
+    seed,
+    size,
+    input: content,
+    outcomes,
+    minimized: None,
+  }))
+}
+
+/// Look for a smaller size (and a few neighbour seeds) that still fails, then remove what is not needed in its input
+fn shrink(day: u32, variants: &[(&'static str, Solver)], mut failure: Counterexample) -> Result<Counterexample> {
+  'shrink: loop {
+    let size = failure.size;
+    let mut sizes = vec![size / 2, size * 3 / 4, size - 1];
+    sizes.retain(|candidate| *candidate >= 1 && *candidate < size);
+    sizes.dedup();
+    for candidate in sizes {
+      for seed in failure.seed..failure.seed + 4 {
+        if let Some(smaller) = try_case(day, variants, seed, candidate)? {
+          failure = smaller;
+          continue 'shrink;
+        }
+      }
+    }
+    let path = format!("day{:02}_test_property", day);
+    let minimized = minimize::minimize_with(day, variants, path.as_ref(), &failure.input, |outcomes| {
+      !agree(outcomes)
+    });
+    failure.minimized = minimized.ok().map(|minimized| minimized.content);
+    return Ok(failure);
+  }
+}
+
+/// Run `config.cases` generated inputs through every variant of `day`.
+/// Sizes are log-uniform so small inputs (easy to read) are tried as often as big ones.
+pub fn check_day(day: u32, config: &Config) -> Result<Option<Counterexample>> {
+  let variants = registry::variants(day);
+  let default_size = generate::default_size(day).ok_or(format!("No generator for day {}", day))?;
+  let max_size = config.max_size.unwrap_or(default_size).clamp(1, default_size);
+  let mut rng = Rng::new(config.seed ^ day as u64);
+  for _ in 0..config.cases {
+    let exponent = rng.below(1024) as f64 / 1023. * (max_size as f64).ln();
+    let size = (exponent.exp().round() as usize).clamp(1, max_size);
+    let seed = rng.next_u64();
+    if let Some(failure) = try_case(day, &variants, seed, size)? {
+      return shrink(day, &variants, failure).map(Some);
+    }
+  }
+  Ok(None)
+}
+
+/// Days with a generator and more than one variant, the only ones worth checking
+pub fn checkable_days() -> Vec<u32> {
+  generate::DAYS
+    .into_iter()
+    .filter(|day| registry::variants(*day).len() > 1)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn variants_agree() -> Result<()> {
+    let config = Config {
+      cases: 8,
+      seed: 0,
+      max_size: Some(40),
+    };
+    for day in checkable_days() {
+      if let Some(failure) = check_day(day, &config)? {
+        panic!("{}", failure);
+      }
+    }
+    Ok(())
+  }
+}
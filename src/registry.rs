@@ -0,0 +1,108 @@
+use crate::days;
+use crate::input::Input;
+use crate::utils::ReturnType;
+use crate::Result;
+
+/// Solver signature
+/// # Argument
+/// * `input` - problem input
+pub type Solver = fn(input: &Input) -> Result<ReturnType>;
+
+/// Macro to register solvers with
+/// # Arguments
+/// * `func` - function that take file and output part1 and part2 result
+///   Each solver keep its own `Solution` type, it's erased to `ReturnType` here.
+/// # Example
+/// ```ignore
+/// let register = register_command!(day01::day01, day01::day01functional);
+/// ```
+macro_rules! register_command {
+    ( $( $func:expr),+ ) => {
+        {
+          // Intermediate variable to force type. otherwise function type is not generic
+          let reg: Vec<(&str, Solver)> = vec![
+            $((stringify!($func), |input: &Input| -> Result<ReturnType> { Ok($func(input)?.into()) }),)*
+          ];
+          reg
+        }
+    };
+}
+
+/// Every solver with its name, variants of a day follow each other
+#[rustfmt::skip]
+pub fn all() -> Vec<(&'static str, Solver)> {
+  register_command!(
+    days::day01::day01,
+    days::day01::day01_speed,
+    days::day01::day01_stream,
+    days::day02::day02,
+    days::day02::day02_speed,
+    days::day02::day02_stream,
+    days::day02::day02_table,
+    days::day03::day03,
+    days::day03::day03_speed,
+    days::day03::day03_bitset,
+    days::day04::day04,
+    days::day04::day04_speed,
+    days::day04::day04_stream,
+    days::day05::day05,
+    days::day05::day05_speed,
+    days::day06::day06,
+    days::day06::day06_speed,
+    days::day06::day06_stream,
+    days::day07::day07,
+    days::day08::day08,
+    days::day08::day08_speed,
+    days::day09::day09,
+    days::day09::day09_speed,
+    days::day09::day09_stream,
+    days::day10::day10,
+    days::day10::day10_stream,
+    days::day11::day11,
+    days::day11::day11_speed,
+    days::day12::day12,
+    days::day12::day12_speed,
+    // days::day13::day13,
+    days::day14::day14,
+    days::day15::day15,
+    days::day15::day15_speed,
+    days::day15::day15_only_range,
+    days::day16::day16,
+    days::day17::day17,
+    days::day18::day18
+    // days::day19::day19,
+    // days::day20::day20,
+    // days::day21::day21,
+    // days::day22::day22,
+    // days::day23::day23,
+    // days::day24::day24,
+    // days::day25::day25
+  )
+}
+
+/// Day of a solver from its name, `days::day15::day15_speed` -> 15
+pub fn day_of(name: &str) -> Option<u32> {
+  let function = name.rsplit("::").next()?.trim();
+  function.strip_prefix("day")?.get(..2)?.parse().ok()
+}
+
+/// All the variants solving `day`
+pub fn variants(day: u32) -> Vec<(&'static str, Solver)> {
+  all()
+    .into_iter()
+    .filter(|(name, _)| day_of(name) == Some(day))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn day_of_names() {
+    assert_eq!(day_of("days::day15::day15_speed"), Some(15));
+    assert_eq!(day_of("days::day01::day01"), Some(1));
+    assert_eq!(day_of("day1"), None);
+    assert_eq!(variants(9).len(), 3);
+  }
+}
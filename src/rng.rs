@@ -0,0 +1,67 @@
+/// Small seeded pseudo random generator (SplitMix64)
+/// Good enough to generate inputs and shuffle them, the same seed always gives the same sequence.
+/// # Example
+/// ```
+/// use aoc_2022::rng::Rng;
+///
+/// let mut rng = Rng::new(42);
+/// let value = rng.range(10, 20);
+/// assert!((10..20).contains(&value));
+/// assert_eq!(Rng::new(42).range(10, 20), value);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rng {
+  state: u64,
+}
+
+impl Rng {
+  pub fn new(seed: u64) -> Self {
+    Self { state: seed }
+  }
+
+  #[inline]
+  pub fn next_u64(&mut self) -> u64 {
+    self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = self.state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+  }
+
+  /// Value in `0..bound`, `bound` must not be 0
+  #[inline]
+  pub fn below(&mut self, bound: u64) -> u64 {
+    debug_assert!(bound > 0);
+    ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+  }
+
+  /// Value in `low..high`
+  #[inline]
+  pub fn range(&mut self, low: i64, high: i64) -> i64 {
+    debug_assert!(low < high);
+    low + self.below(high.abs_diff(low)) as i64
+  }
+
+  /// Index in `0..len`
+  #[inline]
+  pub fn index(&mut self, len: usize) -> usize {
+    self.below(len as u64) as usize
+  }
+
+  /// True with probability `numerator / denominator`
+  #[inline]
+  pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+    self.below(denominator) < numerator
+  }
+
+  pub fn choose<'a, T>(&mut self, values: &'a [T]) -> &'a T {
+    &values[self.index(values.len())]
+  }
+
+  /// Fisher-Yates shuffle
+  pub fn shuffle<T>(&mut self, values: &mut [T]) {
+    for i in (1..values.len()).rev() {
+      values.swap(i, self.index(i + 1));
+    }
+  }
+}
@@ -0,0 +1,106 @@
+//! Resources used by a command: cpu time, memory, context switches and page faults from `getrusage`.
+//! On Linux the counters are the ones of the calling thread, elsewhere the ones of the whole process.
+use std::fmt;
+use std::time::Duration;
+
+/// Counters of `getrusage`, or their difference between two calls
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+  pub user: Duration,
+  pub system: Duration,
+  /// Peak resident memory of the process in KiB, the kernel only keeps the maximum so it's never a difference
+  pub max_rss: u64,
+  pub voluntary_switches: u64,
+  pub involuntary_switches: u64,
+  pub minor_faults: u64,
+  pub major_faults: u64,
+}
+
+#[cfg(unix)]
+fn duration(time: libc::timeval) -> Duration {
+  Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+}
+
+impl Usage {
+  /// Counters so far, None if `getrusage` fails or isn't available
+  pub fn now() -> Option<Self> {
+    #[cfg(unix)]
+    {
+      #[cfg(target_os = "linux")]
+      let who = libc::RUSAGE_THREAD;
+      #[cfg(not(target_os = "linux"))]
+      let who = libc::RUSAGE_SELF;
+      // SAFETY: rusage is plain data, getrusage fills it
+      let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+      if unsafe { libc::getrusage(who, &mut usage) } != 0 {
+        return None;
+      }
+      // max RSS is in KiB on Linux and in bytes on macOS
+      let max_rss = if cfg!(target_os = "macos") {
+        usage.ru_maxrss as u64 / 1024
+      } else {
+        usage.ru_maxrss as u64
+      };
+      Some(Self {
+        user: duration(usage.ru_utime),
+        system: duration(usage.ru_stime),
+        max_rss,
+        voluntary_switches: usage.ru_nvcsw as u64,
+        involuntary_switches: usage.ru_nivcsw as u64,
+        minor_faults: usage.ru_minflt as u64,
+        major_faults: usage.ru_majflt as u64,
+      })
+    }
+    #[cfg(not(unix))]
+    None
+  }
+
+  /// Resources used since `start`
+  pub fn since(&self, start: &Usage) -> Usage {
+    Usage {
+      user: self.user.saturating_sub(start.user),
+      system: self.system.saturating_sub(start.system),
+      max_rss: self.max_rss,
+      voluntary_switches: self.voluntary_switches.saturating_sub(start.voluntary_switches),
+      involuntary_switches: self.involuntary_switches.saturating_sub(start.involuntary_switches),
+      minor_faults: self.minor_faults.saturating_sub(start.minor_faults),
+      major_faults: self.major_faults.saturating_sub(start.major_faults),
+    }
+  }
+}
+
+impl fmt::Display for Usage {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "user {:>9.3} ms sys {:>9.3} ms max rss {:>8} KiB switches {}/{} (vol/invol) page faults {}/{} (minor/major)",
+      self.user.as_secs_f64() * 1e3,
+      self.system.as_secs_f64() * 1e3,
+      self.max_rss,
+      self.voluntary_switches,
+      self.involuntary_switches,
+      self.minor_faults,
+      self.major_faults
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  #[cfg(unix)]
+  fn page_faults_of_a_big_allocation() {
+    let start = Usage::now().unwrap();
+    // touching 16 MiB of fresh memory faults at least once per 4 KiB page
+    let mut memory = vec![0u8; 16 << 20];
+    for index in (0..memory.len()).step_by(4096) {
+      memory[index] = 1;
+    }
+    std::hint::black_box(&memory);
+    let usage = Usage::now().unwrap().since(&start);
+    assert!(usage.minor_faults + usage.major_faults >= 1, "{}", usage);
+    assert!(usage.max_rss >= 16 << 10, "{}", usage);
+  }
+}
@@ -0,0 +1,285 @@
+//! Empirical complexity: time the variants of a day on generated inputs of growing size
+//! and fit `time = a * bytes^exponent` on a log-log scale.
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::generate;
+use crate::input::Input;
+use crate::registry::Solver;
+use crate::Result;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+  /// First generated size, a sixteenth of the real input size if missing
+  pub min_size: Option<usize>,
+  /// Each size is the previous one multiplied by `factor`
+  pub factor: f64,
+  pub steps: u32,
+  pub seed: u64,
+  /// Use the example parameters of the day (day15 search area)
+  pub example: bool,
+}
+
+impl Default for Config {
+  fn default() -> Self {
+    Self {
+      min_size: None,
+      factor: 2.,
+      steps: 5,
+      seed: 0,
+      example: false,
+    }
+  }
+}
+
+/// Time of one variant on one input
+#[derive(Debug, Clone, Copy)]
+pub struct Measure {
+  pub size: usize,
+  pub bytes: u64,
+  pub seconds: f64,
+}
+
+/// Measures of one variant
+#[derive(Debug, Clone)]
+pub struct Series {
+  pub name: &'static str,
+  pub measures: Vec<Measure>,
+  /// Error of the variant on the first input it failed, no measure is done after it
+  pub error: Option<String>,
+}
+
+impl Series {
+  /// Exponent of the input size in the running time
+  pub fn exponent(&self) -> Option<f64> {
+    let points = self
+      .measures
+      .iter()
+      .map(|measure| (measure.bytes as f64, measure.seconds))
+      .collect::<Vec<_>>();
+    fit_exponent(&points)
+  }
+}
+
+/// Least squares slope of `ln(y)` against `ln(x)`, None without two distinct sizes
+pub fn fit_exponent(points: &[(f64, f64)]) -> Option<f64> {
+  let logs = points
+    .iter()
+    .filter(|(x, y)| *x > 0. && *y > 0.)
+    .map(|(x, y)| (x.ln(), y.ln()))
+    .collect::<Vec<_>>();
+  let count = logs.len() as f64;
+  let mean_x = logs.iter().map(|(x, _)| x).sum::<f64>() / count;
+  let mean_y = logs.iter().map(|(_, y)| y).sum::<f64>() / count;
+  let covariance = logs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>();
+  let variance = logs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>();
+  if logs.len() < 2 || variance < 1e-12 {
+    return None;
+  }
+  Some(covariance / variance)
+}
+
+/// Best time per run of `solver`, runs are repeated for at least a few milliseconds so fast solvers can be measured
+fn time(solver: Solver, input: &Input) -> Result<f64> {
+  const MIN_DURATION: Duration = Duration::from_millis(20);
+  const SAMPLES: u32 = 3;
+  let mut best = f64::MAX;
+  for _ in 0..SAMPLES {
+    let now = Instant::now();
+    let mut runs = 0;
+    while runs == 0 || now.elapsed() < MIN_DURATION {
+      solver(input)?;
+      runs += 1;
+    }
+    best = best.min(now.elapsed().as_secs_f64() / runs as f64);
+    // a single run longer than the minimum duration is already precise enough
+    if runs == 1 {
+      break;
+    }
+  }
+  Ok(best)
+}
+
+/// Generated sizes, without duplicates as small sizes are rounded
+pub fn sizes(day: u32, config: &Config) -> Result<Vec<usize>> {
+  let default_size = generate::default_size(day).ok_or(format!("No generator for day {}", day))?;
+  let min_size = config.min_size.unwrap_or(default_size / 16).max(1);
+  let mut sizes = (0..config.steps)
+    .map(|step| (min_size as f64 * config.factor.powi(step as i32)).round() as usize)
+    .collect::<Vec<_>>();
+  sizes.dedup();
+  Ok(sizes)
+}
+
+/// Time every variant on each generated size
+pub fn measure(day: u32, variants: &[(&'static str, Solver)], config: &Config) -> Result<Vec<Series>> {
+  let mut series = variants
+    .iter()
+    .map(|(name, _)| Series {
+      name,
+      measures: Vec::new(),
+      error: None,
+    })
+    .collect::<Vec<_>>();
+  for size in sizes(day, config)? {
+    let options = generate::Options {
+      seed: config.seed,
+      size: Some(size),
+      example: config.example,
+    };
+    let content = generate::generate(day, &options)?;
+    let name = format!("day{:02}_{}scale", day, if config.example { "test_" } else { "" });
+    let input = Input::from_bytes(name, content.into_bytes());
+    let bytes = input.size()?;
+    for ((_, solver), series) in variants.iter().zip(series.iter_mut()) {
+      if series.error.is_some() {
+        continue;
+      }
+      match time(*solver, &input) {
+        Ok(seconds) => series.measures.push(Measure { size, bytes, seconds }),
+        Err(e) => series.error = Some(format!("size {}: {}", size, e)),
+      }
+    }
+  }
+  Ok(series)
+}
+
+/// Markers of the series in the plot
+const MARKERS: &[u8] = b"abcdefgh";
+
+/// Log-log plot of time against input bytes, one marker per series
+pub fn plot(series: &[Series], width: usize, height: usize) -> String {
+  let points = series
+    .iter()
+    .flat_map(|series| series.measures.iter())
+    .collect::<Vec<_>>();
+  if points.is_empty() {
+    return String::new();
+  }
+  let range = |values: &mut dyn Iterator<Item = f64>| {
+    values.fold((f64::MAX, f64::MIN), |(low, high), value| {
+      (low.min(value), high.max(value))
+    })
+  };
+  let (min_x, max_x) = range(&mut points.iter().map(|measure| (measure.bytes as f64).ln()));
+  let (min_y, max_y) = range(&mut points.iter().map(|measure| measure.seconds.ln()));
+  let scale = |value: f64, low: f64, high: f64, cells: usize| {
+    if high - low < 1e-12 {
+      0
+    } else {
+      (((value - low) / (high - low)) * (cells - 1) as f64).round() as usize
+    }
+  };
+  let mut grid = vec![vec![b' '; width]; height];
+  for (series, marker) in series.iter().zip(MARKERS.iter().cycle()) {
+    for measure in &series.measures {
+      let column = scale((measure.bytes as f64).ln(), min_x, max_x, width);
+      let row = height - 1 - scale(measure.seconds.ln(), min_y, max_y, height);
+      grid[row][column] = if grid[row][column] == b' ' { *marker } else { b'*' };
+    }
+  }
+
+  let mut out = String::new();
+  let labels = [format_seconds(max_y.exp()), format_seconds(min_y.exp())];
+  let label_width = labels.iter().map(|label| label.len()).max().unwrap_or(0);
+  for (index, row) in grid.iter().enumerate() {
+    let label = match index {
+      0 => labels[0].as_str(),
+      _ if index == height - 1 => labels[1].as_str(),
+      _ => "",
+    };
+    out += &format!(
+      "{:>width$} |{}\n",
+      label,
+      String::from_utf8_lossy(row),
+      width = label_width
+    );
+  }
+  out += &format!("{:>width$} +{}\n", "", "-".repeat(width), width = label_width);
+  let low = format!("{} B", min_x.exp().round());
+  let high = format!("{} B", max_x.exp().round());
+  out += &format!(
+    "{:>width$}  {}{:>fill$}\n",
+    "",
+    low,
+    high,
+    width = label_width,
+    fill = width.saturating_sub(low.len())
+  );
+  out
+}
+
+fn format_seconds(seconds: f64) -> String {
+  match seconds {
+    _ if seconds < 1e-3 => format!("{:.1} us", seconds * 1e6),
+    _ if seconds < 1. => format!("{:.1} ms", seconds * 1e3),
+    _ => format!("{:.2} s", seconds),
+  }
+}
+
+/// Measures of a day ready to print
+pub struct Report {
+  pub day: u32,
+  pub series: Vec<Series>,
+}
+
+impl fmt::Display for Report {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (series, marker) in self.series.iter().zip(MARKERS.iter().cycle()) {
+      let exponent = series
+        .exponent()
+        .map_or(String::from("?"), |exponent| format!("{:.2}", exponent));
+      writeln!(f, "[{}] {: <30} exponent {}", *marker as char, series.name, exponent)?;
+      for measure in &series.measures {
+        writeln!(
+          f,
+          "      size {:>8} {:>10} B {:>12}",
+          measure.size,
+          measure.bytes,
+          format_seconds(measure.seconds)
+        )?;
+      }
+      if let Some(error) = &series.error {
+        writeln!(f, "      error at {}", error)?;
+      }
+    }
+    writeln!(f, "\nday{:02} time against input size (log-log)", self.day)?;
+    write!(f, "{}", plot(&self.series, 50, 12))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn exponent_of_power_laws() {
+    let quadratic = (1..8)
+      .map(|n| (n as f64 * 100., 3e-6 * (n as f64 * 100.).powi(2)))
+      .collect::<Vec<_>>();
+    assert!((fit_exponent(&quadratic).unwrap() - 2.).abs() < 1e-9);
+    let constant = (1..8).map(|n| (n as f64 * 100., 0.5)).collect::<Vec<_>>();
+    assert!(fit_exponent(&constant).unwrap().abs() < 1e-9);
+    assert_eq!(fit_exponent(&[(10., 1.)]), None);
+  }
+
+  #[test]
+  fn plot_puts_extremes_in_corners() {
+    let series = Series {
+      name: "days::day01::day01",
+      measures: [(100, 1e-6), (1000, 1e-4)]
+        .iter()
+        .map(|&(bytes, seconds)| Measure {
+          size: 0,
+          bytes,
+          seconds,
+        })
+        .collect(),
+      error: None,
+    };
+    let plot = plot(&[series], 10, 4);
+    let lines = plot.lines().collect::<Vec<_>>();
+    assert!(lines[0].ends_with("|         a"));
+    assert!(lines[3].ends_with("|a         "));
+  }
+}
@@ -0,0 +1,79 @@
+//! Largest values of a collection without sorting all of it
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// The `n` largest values in decreasing order, all of them if there are less than `n`.
+/// Linear time selection, then only the selected values are sorted.
+/// # Example
+/// ```
+/// use aoc_2022::select::largest;
+///
+/// assert_eq!(largest(vec![4, 9, 1, 7, 3], 3), vec![9, 7, 4]);
+/// assert_eq!(largest(vec![4, 9], 3), vec![9, 4]);
+/// ```
+pub fn largest<T: Ord>(mut values: Vec<T>, n: usize) -> Vec<T> {
+  if n == 0 {
+    return Vec::new();
+  }
+  if n < values.len() {
+    values.select_nth_unstable_by(n - 1, |a, b| b.cmp(a));
+    values.truncate(n);
+  }
+  values.sort_unstable_by(|a, b| b.cmp(a));
+  values
+}
+
+/// The `n` largest values of a stream, kept in a min-heap of size `n`
+#[derive(Debug, Clone)]
+pub struct TopN<T: Ord> {
+  n: usize,
+  heap: BinaryHeap<Reverse<T>>,
+}
+
+impl<T: Ord> TopN<T> {
+  pub fn new(n: usize) -> Self {
+    Self {
+      n,
+      heap: BinaryHeap::with_capacity(n + 1),
+    }
+  }
+
+  pub fn push(&mut self, value: T) {
+    if self.heap.len() < self.n {
+      self.heap.push(Reverse(value));
+    } else if let Some(mut smallest) = self.heap.peek_mut() {
+      if value > smallest.0 {
+        smallest.0 = value;
+      }
+    }
+  }
+
+  /// Values kept, in decreasing order
+  pub fn into_sorted_vec(self) -> Vec<T> {
+    // sorted_vec of `Reverse` is in increasing order of `Reverse`, so decreasing values
+    self.heap.into_sorted_vec().into_iter().map(|Reverse(value)| value).collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::rng::Rng;
+
+  #[test]
+  fn selection_and_heap_agree_with_sort() {
+    let mut rng = Rng::new(5);
+    for len in 0..40 {
+      let values = (0..len).map(|_| rng.below(20)).collect::<Vec<_>>();
+      let mut sorted = values.clone();
+      sorted.sort_unstable_by(|a, b| b.cmp(a));
+      for n in 0..6 {
+        let expected = sorted.iter().copied().take(n).collect::<Vec<_>>();
+        assert_eq!(largest(values.clone(), n), expected);
+        let mut top = TopN::new(n);
+        values.iter().for_each(|value| top.push(*value));
+        assert_eq!(top.into_sorted_vec(), expected);
+      }
+    }
+  }
+}
@@ -6,21 +6,103 @@
 // I don't find another way to better do that.
 pub extern crate paste;
 
-/// Macro to add test given an input filename and expected value for part1 and part2
+use std::fmt::Debug;
+
+use crate::input::Input;
+use crate::utils::Unsolved;
+
+/// Example input embedded in the test module, it always runs
+pub struct Example(pub &'static str);
+
+/// Where a test get its input from
+/// A path to a private input (`data/` is not committed) gives no input when the file is missing, the test is skipped.
+pub trait TestInput {
+  fn open(self, name: &str) -> Option<Input>;
+}
+
+impl TestInput for &str {
+  fn open(self, _name: &str) -> Option<Input> {
+    if std::path::Path::new(self).exists() {
+      Some(Input::from_path(self))
+    } else {
+      eprintln!("skipped: {} not found", self);
+      None
+    }
+  }
+}
+
+impl TestInput for Example {
+  /// `name` is used as path, solvers looking for `test` in the path get the example parameters
+  fn open(self, name: &str) -> Option<Input> {
+    Some(Input::from_bytes(name, self.0.as_bytes().to_vec()))
+  }
+}
+
+/// Expected value of a part, the type must match the part type of the solver so a wrong expectation doesn't compile
+/// `Unsolved` can be expected for any part, the part is then not checked.
+pub trait Expected<Actual>: Debug {
+  fn matches(&self, actual: &Actual) -> bool;
+}
+
+impl Expected<u64> for u64 {
+  fn matches(&self, actual: &u64) -> bool {
+    self == actual
+  }
+}
+
+impl Expected<String> for &str {
+  fn matches(&self, actual: &String) -> bool {
+    self == actual
+  }
+}
+
+impl<Actual> Expected<Actual> for Unsolved {
+  fn matches(&self, _actual: &Actual) -> bool {
+    true
+  }
+}
+
+/// Check one part of a solution, the failure message give the input file and both values
+#[track_caller]
+pub fn check_part<Actual: Debug, E: Expected<Actual>>(filename: &str, part: u32, actual: &Actual, expected: E) {
+  assert!(
+    expected.matches(actual),
+    "{}: part{} is wrong\n  expected: {:?}\n    actual: {:?}",
+    filename,
+    part,
+    expected,
+    actual
+  );
+}
+
+/// Macro to add test given an input and expected value for part1 and part2
+/// The input is either the filename of a private input, skipped when missing, or an embedded `Example`.
+/// Expected values are checked against the solver part types at compile time,
+/// use `Unsolved` for a part not solved yet.
 /// # Example
 /// ```
 ///   add_test!(
-///     test1:  day01, "data/day01_test1.txt", [7, 5];
-///     test2:  day01, "data/day01_test1.txt", [7, 5];
+///     main:   day01, "data/day01.txt",  [70720, 207148];
+///     test1:  day01, Example(EXAMPLE),  [24000, 45000];
+///     test1:  day05, Example(EXAMPLE),  ["CMZ", "MCD"];
+///     test1:  day18, Example(EXAMPLE),  [64, Unsolved];
 ///   );
 /// ```
 macro_rules! add_test {
-    ($($name:ident: $func:ident, $filename:expr, $value:expr;)*) => {
+    ($($name:ident: $func:ident, $input:expr, [$part1:expr, $part2:expr];)*) => {
     $(
         $crate::test_helper::paste::paste! {
             #[test]
             fn [<$name $func>]() -> $crate::Result<()> {
-                assert_eq!($func(Path::new($filename))?, $value);
+                let name = concat!(stringify!($func), "_", stringify!($name));
+                let input = match $crate::test_helper::TestInput::open($input, name) {
+                    Some(input) => input,
+                    None => return Ok(()),
+                };
+                let name = input.path().display().to_string();
+                let solution = $func(&input)?;
+                $crate::test_helper::check_part(&name, 1, &solution.part1, $part1);
+                $crate::test_helper::check_part(&name, 2, &solution.part2, $part2);
                 Ok(())
             }
         }
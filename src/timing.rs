@@ -0,0 +1,143 @@
+//! Nanosecond timing with a cycle count for the runner.
+//! On x86_64 cycles come from the time stamp counter, elsewhere they are estimated from the elapsed time
+//! and a frequency calibrated once with a chain of dependent multiplications.
+use std::hint::black_box;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Time stamp counter, None when the target has none
+#[inline(always)]
+pub fn time_stamp_counter() -> Option<u64> {
+  #[cfg(target_arch = "x86_64")]
+  // SAFETY: rdtsc is available on every x86_64 cpu, lfence keeps the previous instructions out of the measure
+  unsafe {
+    std::arch::x86_64::_mm_lfence();
+    Some(std::arch::x86_64::_rdtsc())
+  }
+  #[cfg(not(target_arch = "x86_64"))]
+  None
+}
+
+/// Cycles per nanosecond estimated from a chain of multiplications.
+/// Each one waits for the previous one, assuming a 3 cycles latency (true on most current cores).
+fn calibrate_with_multiplications() -> f64 {
+  const MULTIPLICATIONS: u64 = 30_000_000;
+  const LATENCY: f64 = 3.;
+  let mut value = black_box(1u64);
+  let now = Instant::now();
+  for _ in 0..MULTIPLICATIONS {
+    // an opaque multiplier each time, or the multiplications are merged
+    value = value.wrapping_mul(black_box(0x9e3779b97f4a7c15));
+  }
+  black_box(value);
+  MULTIPLICATIONS as f64 * LATENCY / now.elapsed().as_nanos() as f64
+}
+
+/// Time stamp counter ticks per nanosecond, measured against `Instant`
+fn calibrate_time_stamp_counter() -> Option<f64> {
+  const DURATION: Duration = Duration::from_millis(20);
+  let start = time_stamp_counter()?;
+  let now = Instant::now();
+  while now.elapsed() < DURATION {}
+  let ticks = time_stamp_counter()? - start;
+  Some(ticks as f64 / now.elapsed().as_nanos() as f64)
+}
+
+/// Where the cycle counts come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CycleSource {
+  TimeStampCounter,
+  /// Elapsed time multiplied by a calibrated frequency
+  Estimated,
+}
+
+/// Frequency of the cycle counts in cycles per nanosecond (GHz), calibrated on the first call
+pub fn frequency() -> (f64, CycleSource) {
+  static FREQUENCY: OnceLock<(f64, CycleSource)> = OnceLock::new();
+  *FREQUENCY.get_or_init(|| match calibrate_time_stamp_counter() {
+    Some(frequency) => (frequency, CycleSource::TimeStampCounter),
+    None => (calibrate_with_multiplications(), CycleSource::Estimated),
+  })
+}
+
+/// Duration of a measure
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timing {
+  pub nanos: u128,
+  pub cycles: f64,
+}
+
+impl Timing {
+  pub fn cycles_per_byte(&self, bytes: u64) -> f64 {
+    self.cycles / bytes.max(1) as f64
+  }
+
+  /// Throughput in MB/s (10^6 bytes)
+  pub fn megabytes_per_second(&self, bytes: u64) -> f64 {
+    bytes as f64 * 1e3 / self.nanos.max(1) as f64
+  }
+}
+
+/// Started measure
+#[derive(Debug, Clone, Copy)]
+pub struct Timer {
+  start: Instant,
+  start_cycles: Option<u64>,
+}
+
+impl Timer {
+  /// Call `frequency` before starting a timer, the calibration isn't free
+  #[inline(always)]
+  pub fn start() -> Self {
+    Self {
+      start: Instant::now(),
+      start_cycles: time_stamp_counter(),
+    }
+  }
+
+  #[inline(always)]
+  pub fn stop(&self) -> Timing {
+    let end_cycles = time_stamp_counter();
+    let nanos = self.start.elapsed().as_nanos();
+    let cycles = match (self.start_cycles, end_cycles) {
+      (Some(start), Some(end)) => end.wrapping_sub(start) as f64,
+      _ => nanos as f64 * frequency().0,
+    };
+    Timing { nanos, cycles }
+  }
+}
+
+/// Duration in nanoseconds with a readable unit
+pub fn format_nanos(nanos: f64) -> String {
+  match nanos {
+    _ if nanos < 1e3 => format!("{:.1} ns", nanos),
+    _ if nanos < 1e6 => format!("{:.2} us", nanos / 1e3),
+    _ if nanos < 1e9 => format!("{:.2} ms", nanos / 1e6),
+    _ => format!("{:.2} s", nanos / 1e9),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn calibrated_frequencies() {
+    // any cpu running the tests is between 100 MHz and 10 GHz
+    let (frequency, _) = frequency();
+    assert!((0.1..10.).contains(&frequency), "{}", frequency);
+    let estimated = calibrate_with_multiplications();
+    assert!((0.1..10.).contains(&estimated), "{}", estimated);
+  }
+
+  #[test]
+  fn throughput() {
+    let timing = Timing {
+      nanos: 2_000,
+      cycles: 6_000.,
+    };
+    assert_eq!(timing.cycles_per_byte(3_000), 2.);
+    assert_eq!(timing.megabytes_per_second(3_000), 1_500.);
+    assert_eq!(format_nanos(1_500.), "1.50 us");
+  }
+}
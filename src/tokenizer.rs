@@ -0,0 +1,273 @@
+use memchr::memchr;
+
+/// Error returned when a number can't be parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+  /// No digit found
+  Empty,
+  /// Unexpected byte in the number
+  InvalidDigit(u8),
+  /// Number doesn't fit in the integer type
+  Overflow,
+}
+
+impl std::fmt::Display for ParseError {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match self {
+      ParseError::Empty => write!(f, "Expected a number"),
+      ParseError::InvalidDigit(byte) => write!(f, "Invalid digit {:?}", *byte as char),
+      ParseError::Overflow => write!(f, "Number too large"),
+    }
+  }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse an unsigned integer, all bytes must be digits
+/// It's the equivalent of `str::parse::<u64>` without the utf-8 and sign handling.
+#[inline]
+pub fn parse_u64(bytes: &[u8]) -> Result<u64, ParseError> {
+  if bytes.is_empty() {
+    return Err(ParseError::Empty);
+  }
+  let mut value: u64 = 0;
+  for &byte in bytes {
+    let digit = byte.wrapping_sub(b'0');
+    if digit > 9 {
+      return Err(ParseError::InvalidDigit(byte));
+    }
+    value = value
+      .checked_mul(10)
+      .and_then(|value| value.checked_add(digit as u64))
+      .ok_or(ParseError::Overflow)?;
+  }
+  Ok(value)
+}
+
+/// Parse a signed integer with an optional leading `-` or `+`
+#[inline]
+pub fn parse_i64(bytes: &[u8]) -> Result<i64, ParseError> {
+  match bytes.first() {
+    Some(b'-') => {
+      let value = parse_u64(&bytes[1..])?;
+      0i64.checked_sub_unsigned(value).ok_or(ParseError::Overflow)
+    }
+    Some(b'+') => i64::try_from(parse_u64(&bytes[1..])?).map_err(|_| ParseError::Overflow),
+    _ => i64::try_from(parse_u64(bytes)?).map_err(|_| ParseError::Overflow),
+  }
+}
+
+/// Zero-copy cursor over a byte buffer
+/// `read_*` methods parse at the current position, `next_*` methods first skip bytes up to the next number.
+/// # Example
+/// ```
+/// use aoc_2022::tokenizer::Tokenizer;
+///
+/// let mut tokens = Tokenizer::new(b"move 1 from -2 to 3");
+/// assert_eq!(tokens.token(b' '), Some(&b"move"[..]));
+/// assert_eq!(tokens.read_u64().unwrap(), 1);
+/// assert_eq!(tokens.next_i64(), Some(-2));
+/// assert_eq!(tokens.next_u64(), Some(3));
+/// assert!(tokens.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tokenizer<'a> {
+  data: &'a [u8],
+  position: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+  pub fn new(data: &'a [u8]) -> Self {
+    Self { data, position: 0 }
+  }
+
+  #[inline(always)]
+  pub fn is_empty(&self) -> bool {
+    self.position >= self.data.len()
+  }
+
+  #[inline(always)]
+  pub fn position(&self) -> usize {
+    self.position
+  }
+
+  /// Bytes not consumed yet
+  #[inline(always)]
+  pub fn remaining(&self) -> &'a [u8] {
+    &self.data[self.position.min(self.data.len())..]
+  }
+
+  #[inline(always)]
+  pub fn peek(&self) -> Option<u8> {
+    self.data.get(self.position).copied()
+  }
+
+  #[inline(always)]
+  pub fn next_byte(&mut self) -> Option<u8> {
+    let byte = self.peek()?;
+    self.position += 1;
+    Some(byte)
+  }
+
+  /// Skip `count` bytes
+  #[inline(always)]
+  pub fn skip(&mut self, count: usize) {
+    self.position = (self.position + count).min(self.data.len());
+  }
+
+  /// Skip `expected` if it's the next byte
+  #[inline(always)]
+  pub fn skip_byte(&mut self, expected: u8) -> bool {
+    if self.peek() == Some(expected) {
+      self.position += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Skip `expected` if the remaining bytes start with it
+  #[inline]
+  pub fn skip_prefix(&mut self, expected: &[u8]) -> bool {
+    if self.remaining().starts_with(expected) {
+      self.position += expected.len();
+      true
+    } else {
+      false
+    }
+  }
+
+  #[inline]
+  pub fn skip_while(&mut self, predicate: impl Fn(u8) -> bool) {
+    while let Some(byte) = self.peek() {
+      if !predicate(byte) {
+        break;
+      }
+      self.position += 1;
+    }
+  }
+
+  /// Move just after the next `delimiter`, or to the end if there is none
+  #[inline]
+  pub fn skip_past(&mut self, delimiter: u8) {
+    self.position = match memchr(delimiter, self.remaining()) {
+      Some(offset) => self.position + offset + 1,
+      None => self.data.len(),
+    };
+  }
+
+  /// Bytes up to the next `delimiter` (excluded), the delimiter is consumed
+  /// Return None when there is nothing left.
+  #[inline]
+  pub fn token(&mut self, delimiter: u8) -> Option<&'a [u8]> {
+    if self.is_empty() {
+      return None;
+    }
+    let remaining = self.remaining();
+    let token = match memchr(delimiter, remaining) {
+      Some(offset) => &remaining[..offset],
+      None => remaining,
+    };
+    self.position += token.len() + 1;
+    Some(token)
+  }
+
+  /// Next line without its line ending (`\n` or `\r\n`)
+  #[inline]
+  pub fn line(&mut self) -> Option<&'a [u8]> {
+    let line = self.token(b'\n')?;
+    Some(line.strip_suffix(b"\r").unwrap_or(line))
+  }
+
+  /// Parse the digits at the current position
+  #[inline]
+  pub fn read_u64(&mut self) -> Result<u64, ParseError> {
+    let start = self.position;
+    self.skip_while(|byte| byte.is_ascii_digit());
+    parse_u64(&self.data[start..self.position])
+  }
+
+  /// Parse a number with an optional `-` at the current position
+  #[inline]
+  pub fn read_i64(&mut self) -> Result<i64, ParseError> {
+    let start = self.position;
+    self.skip_byte(b'-');
+    self.skip_while(|byte| byte.is_ascii_digit());
+    parse_i64(&self.data[start..self.position])
+  }
+
+  /// Skip to the next digit and parse the number, None if there is no more number or it doesn't fit
+  #[inline]
+  pub fn next_u64(&mut self) -> Option<u64> {
+    self.skip_while(|byte| !byte.is_ascii_digit());
+    if self.is_empty() {
+      return None;
+    }
+    self.read_u64().ok()
+  }
+
+  /// Skip to the next digit or `-` followed by a digit and parse the number
+  #[inline]
+  pub fn next_i64(&mut self) -> Option<i64> {
+    loop {
+      self.skip_while(|byte| !byte.is_ascii_digit() && byte != b'-');
+      match self.peek()? {
+        b'-' if !self.data.get(self.position + 1)?.is_ascii_digit() => self.position += 1,
+        _ => return self.read_i64().ok(),
+      }
+    }
+  }
+}
+
+/// Iterator over lines of a byte buffer, line ending (`\n` or `\r\n`) is removed
+pub struct Lines<'a>(Tokenizer<'a>);
+
+impl<'a> Iterator for Lines<'a> {
+  type Item = &'a [u8];
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    self.0.line()
+  }
+}
+
+/// Equivalent of `str::lines` on bytes
+pub fn lines(data: &[u8]) -> Lines<'_> {
+  Lines(Tokenizer::new(data))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_numbers() {
+    assert_eq!(parse_u64(b"0"), Ok(0));
+    assert_eq!(parse_u64(b"18446744073709551615"), Ok(u64::MAX));
+    assert_eq!(parse_u64(b"18446744073709551616"), Err(ParseError::Overflow));
+    assert_eq!(parse_u64(b""), Err(ParseError::Empty));
+    assert_eq!(parse_u64(b"12a"), Err(ParseError::InvalidDigit(b'a')));
+    assert_eq!(parse_i64(b"-9223372036854775808"), Ok(i64::MIN));
+    assert_eq!(parse_i64(b"9223372036854775808"), Err(ParseError::Overflow));
+    assert_eq!(parse_i64(b"-"), Err(ParseError::Empty));
+    assert_eq!(parse_i64(b"+42"), Ok(42));
+  }
+
+  #[test]
+  fn lines_like_std() {
+    let text = "a\r\n\nbc\nd";
+    let expected = text.lines().map(|line| line.as_bytes()).collect::<Vec<_>>();
+    assert_eq!(lines(text.as_bytes()).collect::<Vec<_>>(), expected);
+    assert_eq!(lines(b"a\n").count(), 1);
+    assert_eq!(lines(b"").count(), 0);
+  }
+
+  #[test]
+  fn next_signed() {
+    let mut tokens = Tokenizer::new(b"Sensor at x=-2, y=18: - -x -3");
+    assert_eq!(tokens.next_i64(), Some(-2));
+    assert_eq!(tokens.next_i64(), Some(18));
+    assert_eq!(tokens.next_i64(), Some(-3));
+    assert_eq!(tokens.next_i64(), None);
+  }
+}
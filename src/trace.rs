@@ -0,0 +1,215 @@
+//! Timeline of a run in the Chrome trace-event format (chrome://tracing, https://ui.perfetto.dev).
+//! The runner records a span per command, solvers can add spans for their phases (parse, part1, part2).
+//! Nothing is recorded until `enable` is called, a span then only costs an atomic load.
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use crate::Result;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+static EVENTS: Mutex<Vec<Event>> = Mutex::new(Vec::new());
+static NEXT_TRACK: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+  /// Track of the thread in the trace, 0 until the thread records its first span
+  static TRACK: Cell<u64> = const { Cell::new(0) };
+}
+
+#[derive(Debug, Clone)]
+enum Event {
+  /// Name of a track
+  Thread { track: u64, name: String },
+  Span {
+    name: Cow<'static, str>,
+    category: &'static str,
+    track: u64,
+    /// Nanoseconds since `enable`
+    start: u128,
+    duration: u128,
+    args: Vec<(&'static str, String)>,
+  },
+}
+
+/// Start recording
+pub fn enable() {
+  EPOCH.get_or_init(Instant::now);
+  ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+  ENABLED.load(Ordering::Relaxed)
+}
+
+fn record(event: Event) {
+  if let Ok(mut events) = EVENTS.lock() {
+    events.push(event);
+  }
+}
+
+/// Track of the current thread, named after the thread the first time
+fn track() -> u64 {
+  TRACK.with(|track| {
+    if track.get() == 0 {
+      track.set(NEXT_TRACK.fetch_add(1, Ordering::Relaxed));
+      let thread = std::thread::current();
+      let name = thread
+        .name()
+        .map_or_else(|| format!("thread {}", track.get()), String::from);
+      record(Event::Thread {
+        track: track.get(),
+        name,
+      });
+    }
+    track.get()
+  })
+}
+
+/// Span recorded when dropped, or ended with `end`
+#[must_use = "the span ends when dropped"]
+pub struct Span {
+  started: Option<(Instant, Cow<'static, str>, &'static str)>,
+  args: Vec<(&'static str, String)>,
+}
+
+impl Span {
+  pub fn new(name: impl Into<Cow<'static, str>>, category: &'static str) -> Self {
+    Self {
+      started: is_enabled().then(|| (Instant::now(), name.into(), category)),
+      args: Vec::new(),
+    }
+  }
+
+  /// Value shown with the span in the viewer
+  pub fn arg(mut self, key: &'static str, value: impl ToString) -> Self {
+    if self.started.is_some() {
+      self.args.push((key, value.to_string()));
+    }
+    self
+  }
+
+  pub fn end(self) {}
+}
+
+impl Drop for Span {
+  fn drop(&mut self) {
+    if let (Some((start, name, category)), Some(epoch)) = (self.started.take(), EPOCH.get()) {
+      record(Event::Span {
+        name,
+        category,
+        track: track(),
+        start: start.duration_since(*epoch).as_nanos(),
+        duration: start.elapsed().as_nanos(),
+        args: std::mem::take(&mut self.args),
+      });
+    }
+  }
+}
+
+/// Phase of a solver
+/// # Example
+/// ```
+/// use aoc_2022::trace;
+///
+/// let parse = trace::span("parse");
+/// let values = "1 2 3".split(' ').map(|value| value.parse::<u32>()).collect::<Vec<_>>();
+/// parse.end();
+/// ```
+pub fn span(name: &'static str) -> Span {
+  Span::new(name, "phase")
+}
+
+fn escape(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      c if (c as u32) < 0x20 => {
+        let _ = write!(out, "\\u{:04x}", c as u32);
+      }
+      c => out.push(c),
+    }
+  }
+  out
+}
+
+/// Recorded events as a trace-event JSON document, timestamps in microseconds
+pub fn to_json() -> String {
+  let events = EVENTS.lock().map(|events| events.clone()).unwrap_or_default();
+  let lines = events
+    .iter()
+    .map(|event| match event {
+      Event::Thread { track, name } => format!(
+        r#"{{"name":"thread_name","ph":"M","pid":1,"tid":{},"args":{{"name":"{}"}}}}"#,
+        track,
+        escape(name)
+      ),
+      Event::Span {
+        name,
+        category,
+        track,
+        start,
+        duration,
+        args,
+      } => {
+        let args = args
+          .iter()
+          .map(|(key, value)| format!(r#""{}":"{}""#, key, escape(value)))
+          .collect::<Vec<_>>()
+          .join(",");
+        format!(
+          r#"{{"name":"{}","cat":"{}","ph":"X","pid":1,"tid":{},"ts":{:.3},"dur":{:.3},"args":{{{}}}}}"#,
+          escape(name),
+          category,
+          track,
+          *start as f64 / 1e3,
+          *duration as f64 / 1e3,
+          args
+        )
+      }
+    })
+    .collect::<Vec<_>>();
+  format!(
+    "{{\"displayTimeUnit\":\"ns\",\"traceEvents\":[\n{}\n]}}\n",
+    lines.join(",\n")
+  )
+}
+
+pub fn write(path: &Path) -> Result<()> {
+  std::fs::write(path, to_json())?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn spans_are_recorded_once_enabled() {
+    let ignored = span("before enable");
+    ignored.end();
+    enable();
+    std::thread::Builder::new()
+      .name(String::from("worker"))
+      .spawn(|| {
+        let command = Span::new(String::from("days::day01::day01"), "command").arg("input", "data/\"day01\".txt");
+        span("parse").end();
+        command.end();
+      })
+      .unwrap()
+      .join()
+      .unwrap();
+    let json = to_json();
+    assert!(!json.contains("before enable"));
+    assert!(json.contains(r#""ph":"M","pid":1,"tid":"#) && json.contains(r#""args":{"name":"worker"}"#));
+    assert!(json.contains(r#""name":"days::day01::day01","cat":"command","ph":"X""#));
+    assert!(json.contains(r#""args":{"input":"data/\"day01\".txt"}"#));
+    assert!(json.contains(r#""name":"parse","cat":"phase""#));
+  }
+}
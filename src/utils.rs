@@ -1,28 +1,169 @@
-#[derive(Debug, PartialEq)]
-pub enum ReturnType {
-  Numeric(u64, u64),
-  String(String, String),
+/// Answers of a solver, each part keep its own type
+/// A part is usually `u64` or `String`, [`Unsolved`] is used while a part isn't implemented yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution<P1, P2> {
+  pub part1: P1,
+  pub part2: P2,
 }
 
-impl PartialEq<[u64; 2]> for ReturnType {
-  fn eq(&self, other: &[u64; 2]) -> bool {
+impl<P1, P2> Solution<P1, P2> {
+  pub fn new(part1: P1, part2: P2) -> Self {
+    Self { part1, part2 }
+  }
+}
+
+/// Placeholder answer of a part not solved yet
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Unsolved;
+
+impl std::fmt::Display for Unsolved {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "unsolved")
+  }
+}
+
+/// Answer of one part once its type is erased
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+  Numeric(u64),
+  Text(String),
+  Unsolved,
+}
+
+impl std::fmt::Display for Answer {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
     match self {
-      ReturnType::Numeric(part1, part2) => [*part1, *part2] == *other,
-      ReturnType::String(_, _) => {
-        panic!("Should not compare ReturnType::String with [u64; 2]");
-      }
+      Answer::Numeric(value) => write!(f, "{}", value),
+      Answer::Text(value) => write!(f, "{}", value),
+      Answer::Unsolved => write!(f, "{}", Unsolved),
     }
   }
 }
 
-impl PartialEq<[&str; 2]> for ReturnType {
-  fn eq(&self, other: &[&str; 2]) -> bool {
+impl From<u64> for Answer {
+  fn from(value: u64) -> Self {
+    Answer::Numeric(value)
+  }
+}
+
+impl From<String> for Answer {
+  fn from(value: String) -> Self {
+    Answer::Text(value)
+  }
+}
+
+impl From<Unsolved> for Answer {
+  fn from(_: Unsolved) -> Self {
+    Answer::Unsolved
+  }
+}
+
+/// Type erased [`Solution`], the runner use it to handle all solvers the same way
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReturnType {
+  pub part1: Answer,
+  pub part2: Answer,
+}
+
+impl<P1: Into<Answer>, P2: Into<Answer>> From<Solution<P1, P2>> for ReturnType {
+  fn from(solution: Solution<P1, P2>) -> Self {
+    Self {
+      part1: solution.part1.into(),
+      part2: solution.part2.into(),
+    }
+  }
+}
+
+/// Error returned by [`get_many_mut`] when the requested indexes can't be borrowed together
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GetManyMutError {
+  IndexOutOfBounds { index: usize, len: usize },
+  OverlappingIndices { index: usize },
+}
+
+impl std::fmt::Display for GetManyMutError {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
     match self {
-      ReturnType::Numeric(_, _) => {
-        // Best way I found, I would have prefer a compilation error but I don't found how
-        panic!("Should not compare ReturnType::Numeric with [&str; 2]");
+      GetManyMutError::IndexOutOfBounds { index, len } => {
+        write!(f, "Index {} out of bounds for slice of length {}", index, len)
       }
-      ReturnType::String(part1, part2) => [&part1[..], &part2[..]] == *other,
+      GetManyMutError::OverlappingIndices { index } => write!(f, "Index {} is requested more than once", index),
+    }
+  }
+}
+
+impl std::error::Error for GetManyMutError {}
+
+/// Like split_at_mut but we give N elems instead of 2 slices
+/// Indexes are checked to be in bounds and pairwise distinct, for small N it's as fast as the unchecked version
+/// # Example
+/// ```
+/// let mut values = [1, 2, 3];
+/// let [a, c] = aoc_2022::utils::get_many_mut(&mut values, [0, 2]).unwrap();
+/// std::mem::swap(a, c);
+/// assert_eq!(values, [3, 2, 1]);
+/// ```
+#[inline(always)]
+pub fn get_many_mut<T, const N: usize>(
+  values: &mut [T],
+  indexes: [usize; N],
+) -> Result<[&mut T; N], GetManyMutError> {
+  let len = values.len();
+  for (position, &index) in indexes.iter().enumerate() {
+    if index >= len {
+      return Err(GetManyMutError::IndexOutOfBounds { index, len });
+    }
+    if indexes[..position].contains(&index) {
+      return Err(GetManyMutError::OverlappingIndices { index });
     }
   }
+  // SAFETY: all indexes are in bounds and pairwise distinct
+  Ok(unsafe { get_many_unchecked_mut(values, indexes) })
+}
+
+/// Unchecked version of [`get_many_mut`], checks are only done in debug build
+/// # Safety
+/// Indexes must be in bounds and pairwise distinct
+#[inline(always)]
+pub unsafe fn get_many_unchecked_mut<T, const N: usize>(values: &mut [T], indexes: [usize; N]) -> [&mut T; N] {
+  debug_assert!(indexes.iter().all(|&index| index < values.len()));
+  debug_assert!(indexes
+    .iter()
+    .enumerate()
+    .all(|(position, index)| !indexes[..position].contains(index)));
+  let ptr = values.as_mut_ptr();
+  indexes.map(|index| &mut *ptr.add(index))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn get_many_mut_disjoint() {
+    let mut values = [0, 1, 2, 3];
+    let [a, b, c] = get_many_mut(&mut values, [3, 0, 1]).unwrap();
+    *a += 10;
+    *b += 20;
+    *c += 30;
+    assert_eq!(values, [20, 31, 2, 13]);
+  }
+
+  #[test]
+  fn get_many_mut_out_of_bounds() {
+    let mut values = [0, 1, 2];
+    assert_eq!(
+      get_many_mut(&mut values, [0, 3]),
+      Err(GetManyMutError::IndexOutOfBounds { index: 3, len: 3 })
+    );
+  }
+
+  #[test]
+  fn get_many_mut_overlapping() {
+    let mut values = [0, 1, 2];
+    assert_eq!(
+      get_many_mut(&mut values, [1, 2, 1]),
+      Err(GetManyMutError::OverlappingIndices { index: 1 })
+    );
+  }
 }